@@ -1,5 +1,8 @@
 fn main() {
-    slint_build::compile("ui/appwindow.slint").unwrap();
+    // 测试通过 ElementHandle 按读屏标签查找控件，需要在生成的代码中保留元素信息
+    let debug = std::env::var("PROFILE").is_ok_and(|profile| profile == "debug");
+    let config = slint_build::CompilerConfiguration::new().with_debug_info(debug);
+    slint_build::compile_with_config("ui/appwindow.slint", config).unwrap();
 
     // Windows: embed app icon into the exe
     #[cfg(target_os = "windows")]
@@ -0,0 +1,650 @@
+//! 提示音与休息环境音
+//!
+//! 内置声音以 WAV 形式编译进程序。Linux 通过 `pacat`（PulseAudio / PipeWire）或
+//! `aplay`（ALSA）播放，两者都没有安装时启动时提示并保持静音；Windows 使用 `PlaySound`。
+//! 设置环境变量 `ARE_YOU_BLIND_AUDIO=null` 时不发出任何声音，方便测试。
+
+use crate::config::SoundSettings;
+use std::sync::Arc;
+
+/// 程序会播放的声音
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sound {
+    /// 护眼休息开始
+    RestStart,
+    /// 喝水提醒开始
+    Water,
+    /// 走动提醒开始
+    Walk,
+    /// 休息结束
+    RestEnd,
+    /// 休息期间循环的环境音
+    Ambient,
+}
+
+impl Sound {
+    /// `[sound.files]` 中替换该声音时使用的键
+    fn key(self) -> &'static str {
+        match self {
+            Sound::RestStart => "rest_start",
+            Sound::Water => "water",
+            Sound::Walk => "walk",
+            Sound::RestEnd => "rest_end",
+            Sound::Ambient => "ambient",
+        }
+    }
+
+    fn bundled(self) -> &'static [u8] {
+        match self {
+            Sound::RestStart => include_bytes!("../assets/sounds/rest_start.wav"),
+            Sound::Water => include_bytes!("../assets/sounds/water.wav"),
+            Sound::Walk => include_bytes!("../assets/sounds/walk.wav"),
+            Sound::RestEnd => include_bytes!("../assets/sounds/rest_end.wav"),
+            Sound::Ambient => include_bytes!("../assets/sounds/ambient.wav"),
+        }
+    }
+}
+
+/// 正在播放的声音，停止或丢弃时结束播放
+pub struct Playback {
+    stop: Option<Box<dyn FnOnce()>>,
+}
+
+impl Playback {
+    pub fn none() -> Self {
+        Self { stop: None }
+    }
+
+    pub fn stop(mut self) {
+        if let Some(stop) = self.stop.take() {
+            stop();
+        }
+    }
+
+    /// 让声音自己播放完
+    pub fn detach(mut self) {
+        self.stop = None;
+    }
+}
+
+impl Drop for Playback {
+    fn drop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            stop();
+        }
+    }
+}
+
+/// 实际发声的后端
+pub trait AudioBackend {
+    /// 播放一段 WAV 数据；`looping` 时一直重复，直到返回的 `Playback` 被停止
+    fn play(&self, wav: Arc<[u8]>, looping: bool) -> Playback;
+}
+
+/// 不发声的后端，用于测试和没有声音设备的环境
+#[derive(Default)]
+pub struct NullAudio;
+
+impl AudioBackend for NullAudio {
+    fn play(&self, _wav: Arc<[u8]>, _looping: bool) -> Playback {
+        Playback::none()
+    }
+}
+
+/// 当前平台的默认后端
+pub fn system_backend() -> Box<dyn AudioBackend> {
+    if std::env::var("ARE_YOU_BLIND_AUDIO").is_ok_and(|value| value == "null") {
+        return Box::new(NullAudio);
+    }
+    #[cfg(target_os = "linux")]
+    return match linux::CommandAudio::detect() {
+        Some(audio) => Box::new(audio),
+        None => {
+            eprintln!("No audio player found, sounds are disabled: install pacat (PulseAudio / PipeWire) or aplay (ALSA)");
+            Box::new(NullAudio)
+        }
+    };
+    #[cfg(windows)]
+    return Box::new(windows::PlaySoundAudio);
+    #[allow(unreachable_code)]
+    Box::new(NullAudio)
+}
+
+/// 按设置选择声音、调整音量，并管理休息期间的环境音
+pub struct Audio {
+    backend: Box<dyn AudioBackend>,
+    ambient: Option<Playback>,
+}
+
+impl Audio {
+    pub fn new(backend: Box<dyn AudioBackend>) -> Self {
+        Self {
+            backend,
+            ambient: None,
+        }
+    }
+
+    /// 休息开始：按提醒类型播放提示音，需要时开始环境音
+    pub fn rest_started(&mut self, settings: &SoundSettings, sound: Sound) {
+        self.stop_ambient();
+        if !settings.enabled {
+            return;
+        }
+        if settings.start_chime {
+            self.play_once(settings, sound);
+        }
+        if settings.ambient {
+            if let Some(wav) = load(settings, Sound::Ambient) {
+                self.ambient = Some(self.backend.play(wav, true));
+            }
+        }
+    }
+
+    /// 休息结束：停止环境音并播放结束提示音
+    pub fn rest_ended(&mut self, settings: &SoundSettings) {
+        self.stop_ambient();
+        if settings.enabled && settings.end_chime {
+            self.play_once(settings, Sound::RestEnd);
+        }
+    }
+
+    pub fn stop_ambient(&mut self) {
+        if let Some(ambient) = self.ambient.take() {
+            ambient.stop();
+        }
+    }
+
+    fn play_once(&self, settings: &SoundSettings, sound: Sound) {
+        if let Some(wav) = load(settings, sound) {
+            self.backend.play(wav, false).detach();
+        }
+    }
+}
+
+/// 读取声音（用户替换的文件优先）并按音量缩放
+fn load(settings: &SoundSettings, sound: Sound) -> Option<Arc<[u8]>> {
+    let custom = settings
+        .files
+        .get(sound.key())
+        .and_then(|path| std::fs::read(path).ok());
+    let volume = settings.volume.clamp(0.0, 1.0);
+    if volume <= 0.0 {
+        return None;
+    }
+    let mut wav = custom.unwrap_or_else(|| sound.bundled().to_vec());
+    scale_volume(&mut wav, volume);
+    Some(wav.into())
+}
+
+/// 按音量缩放 16 位 PCM WAV 的采样；其他格式原样播放
+fn scale_volume(wav: &mut [u8], volume: f32) {
+    let Some(data) = pcm16_data(wav) else {
+        return;
+    };
+    for sample in wav[data].chunks_exact_mut(2) {
+        let value = i16::from_le_bytes([sample[0], sample[1]]) as f32 * volume;
+        sample.copy_from_slice(&(value as i16).to_le_bytes());
+    }
+}
+
+/// 16 位 PCM WAV 中采样数据的字节范围
+fn pcm16_data(wav: &[u8]) -> Option<std::ops::Range<usize>> {
+    let mut pcm16 = false;
+    for (id, body) in chunks(wav) {
+        match id {
+            b"fmt " if body.len() >= 16 => {
+                let format = u16::from_le_bytes([wav[body.start], wav[body.start + 1]]);
+                let bits = u16::from_le_bytes([wav[body.start + 14], wav[body.start + 15]]);
+                pcm16 = format == 1 && bits == 16;
+            }
+            b"data" => return pcm16.then_some(body),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// WAV 的播放时长，按 `fmt ` 块中的每秒字节数计算
+#[cfg_attr(not(windows), allow(dead_code))]
+fn wav_duration(wav: &[u8]) -> Option<std::time::Duration> {
+    let mut byte_rate = 0;
+    for (id, body) in chunks(wav) {
+        match id {
+            b"fmt " if body.len() >= 16 => {
+                byte_rate =
+                    u32::from_le_bytes(wav[body.start + 8..body.start + 12].try_into().ok()?);
+            }
+            b"data" if byte_rate > 0 => {
+                return Some(std::time::Duration::from_secs_f64(
+                    body.len() as f64 / byte_rate as f64,
+                ));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// RIFF WAVE 中的各个块：块 ID 与内容的字节范围（截断在文件末尾）
+fn chunks(wav: &[u8]) -> impl Iterator<Item = (&[u8], std::ops::Range<usize>)> {
+    let riff = wav.len() >= 12 && &wav[0..4] == b"RIFF" && &wav[8..12] == b"WAVE";
+    let mut offset = if riff { 12 } else { wav.len() };
+    std::iter::from_fn(move || {
+        if offset + 8 > wav.len() {
+            return None;
+        }
+        let id = &wav[offset..offset + 4];
+        let size = u32::from_le_bytes(wav[offset + 4..offset + 8].try_into().ok()?) as usize;
+        let body = offset + 8;
+        // 块按偶数字节对齐
+        offset = body.saturating_add(size).saturating_add(size & 1);
+        Some((id, body..body.saturating_add(size).min(wav.len())))
+    })
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::{AudioBackend, Playback};
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::process::{Child, Command, Stdio};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// 检查播放进程是否结束的间隔
+    const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+    /// 依次尝试的播放命令，都从标准输入读取 WAV
+    const PLAYERS: [&[&str]; 2] = [
+        &["pacat", "--playback", "--file-format=wav"],
+        &["aplay", "-q", "-"],
+    ];
+
+    /// 通过 PulseAudio（`pacat`）或 ALSA（`aplay`）命令播放
+    pub struct CommandAudio {
+        player: &'static [&'static str],
+    }
+
+    impl CommandAudio {
+        /// 使用 `PATH` 中找到的第一个播放命令；都没有安装时返回 None
+        pub fn detect() -> Option<Self> {
+            let path = std::env::var_os("PATH")?;
+            let dirs: Vec<PathBuf> = std::env::split_paths(&path).collect();
+            PLAYERS
+                .into_iter()
+                .find(|args| dirs.iter().any(|dir| dir.join(args[0]).is_file()))
+                .map(|player| Self { player })
+        }
+    }
+
+    impl AudioBackend for CommandAudio {
+        fn play(&self, wav: Arc<[u8]>, looping: bool) -> Playback {
+            let player = self.player;
+            let stopped = Arc::new(AtomicBool::new(false));
+            let current: Arc<Mutex<Option<Child>>> = Arc::default();
+
+            let thread_stopped = stopped.clone();
+            let thread_current = current.clone();
+            std::thread::spawn(move || loop {
+                let mut child = match spawn(player) {
+                    Ok(child) => child,
+                    Err(e) => {
+                        eprintln!("Failed to run {}: {}", player[0], e);
+                        return;
+                    }
+                };
+                let stdin = child.stdin.take();
+                *thread_current.lock().unwrap() = Some(child);
+                // 在放入进程之前就被停止了
+                if thread_stopped.load(Ordering::SeqCst) {
+                    if let Some(mut child) = thread_current.lock().unwrap().take() {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                    }
+                    break;
+                }
+                // 播放器按播放速度读取，进程被停止时写入会失败返回
+                if let Some(mut stdin) = stdin {
+                    let _ = stdin.write_all(&wav);
+                }
+
+                // 进程留在共享位置，以便随时被停止
+                loop {
+                    let mut current = thread_current.lock().unwrap();
+                    match current.as_mut().map(|child| child.try_wait()) {
+                        Some(Ok(None)) => {}
+                        Some(_) => {
+                            current.take();
+                            break;
+                        }
+                        None => break,
+                    }
+                    drop(current);
+                    std::thread::sleep(POLL_INTERVAL);
+                }
+                if !looping || thread_stopped.load(Ordering::SeqCst) {
+                    break;
+                }
+            });
+
+            Playback {
+                stop: Some(Box::new(move || {
+                    stopped.store(true, Ordering::SeqCst);
+                    if let Some(mut child) = current.lock().unwrap().take() {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                    }
+                })),
+            }
+        }
+    }
+
+    fn spawn(player: &[&str]) -> std::io::Result<Child> {
+        Command::new(player[0])
+            .args(&player[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    #![allow(clippy::upper_case_acronyms)]
+
+    use super::{wav_duration, AudioBackend, Playback};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Instant;
+
+    type BOOL = i32;
+
+    const SND_ASYNC: u32 = 0x0001;
+    const SND_NODEFAULT: u32 = 0x0002;
+    const SND_MEMORY: u32 = 0x0004;
+    const SND_LOOP: u32 = 0x0008;
+
+    #[link(name = "winmm")]
+    extern "system" {
+        fn PlaySoundW(sound: *const u8, module: isize, flags: u32) -> BOOL;
+    }
+
+    struct Playing {
+        /// `SND_MEMORY` 异步播放时系统直接读取这块内存，播放期间必须保持有效
+        wav: Option<Arc<[u8]>>,
+        /// 最近一次提示音预计播完的时间
+        chime_end: Option<Instant>,
+    }
+
+    static PLAYING: Mutex<Playing> = Mutex::new(Playing {
+        wav: None,
+        chime_end: None,
+    });
+
+    /// `PlaySound` 同一时间只能播放一个声音，新的声音会打断正在播放的；
+    /// 循环的环境音因此等提示音播完再开始
+    pub struct PlaySoundAudio;
+
+    impl AudioBackend for PlaySoundAudio {
+        fn play(&self, wav: Arc<[u8]>, looping: bool) -> Playback {
+            if !looping {
+                let mut playing = PLAYING.lock().unwrap();
+                start(&mut playing.wav, &wav, false);
+                playing.chime_end = wav_duration(&wav).map(|duration| Instant::now() + duration);
+                return Playback::none();
+            }
+
+            let stopped = Arc::new(AtomicBool::new(false));
+            let thread_stopped = stopped.clone();
+            let thread_wav = wav.clone();
+            std::thread::spawn(move || {
+                let chime_end = PLAYING.lock().unwrap().chime_end;
+                if let Some(wait) =
+                    chime_end.and_then(|end| end.checked_duration_since(Instant::now()))
+                {
+                    std::thread::sleep(wait);
+                }
+                // 检查与开始播放在同一把锁内，不会在停止之后才开始
+                let mut playing = PLAYING.lock().unwrap();
+                if !thread_stopped.load(Ordering::SeqCst) {
+                    start(&mut playing.wav, &thread_wav, true);
+                }
+            });
+
+            Playback {
+                stop: Some(Box::new(move || {
+                    let mut playing = PLAYING.lock().unwrap();
+                    stopped.store(true, Ordering::SeqCst);
+                    // 只停止自己；已经被别的声音替换时不打断它
+                    if playing
+                        .wav
+                        .as_ref()
+                        .is_some_and(|current| Arc::ptr_eq(current, &wav))
+                    {
+                        unsafe {
+                            PlaySoundW(std::ptr::null(), 0, 0);
+                        }
+                        playing.wav = None;
+                    }
+                })),
+            }
+        }
+    }
+
+    /// 开始播放并记下正在使用的内存
+    fn start(playing: &mut Option<Arc<[u8]>>, wav: &Arc<[u8]>, looping: bool) {
+        let mut flags = SND_ASYNC | SND_NODEFAULT | SND_MEMORY;
+        if looping {
+            flags |= SND_LOOP;
+        }
+        unsafe {
+            PlaySoundW(wav.as_ptr(), 0, flags);
+        }
+        *playing = Some(wav.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Play(Sound, bool),
+        Stop(Sound),
+    }
+
+    /// 记录播放与停止的后端，按 WAV 内容认出是哪个声音
+    #[derive(Clone, Default)]
+    struct Recorder {
+        events: Rc<RefCell<Vec<Event>>>,
+    }
+
+    impl AudioBackend for Recorder {
+        fn play(&self, wav: Arc<[u8]>, looping: bool) -> Playback {
+            let settings = SoundSettings::default();
+            let sound = [
+                Sound::RestStart,
+                Sound::Water,
+                Sound::Walk,
+                Sound::RestEnd,
+                Sound::Ambient,
+            ]
+            .into_iter()
+            .find(|sound| load(&settings, *sound).as_deref() == Some(&*wav))
+            .expect("unknown sound");
+            self.events.borrow_mut().push(Event::Play(sound, looping));
+            let events = self.events.clone();
+            Playback {
+                stop: Some(Box::new(move || {
+                    events.borrow_mut().push(Event::Stop(sound))
+                })),
+            }
+        }
+    }
+
+    /// `run` 期间的播放记录；之后丢弃 `Audio` 时的停止不计入
+    fn recorded(run: impl FnOnce(&mut Audio)) -> Vec<Event> {
+        let recorder = Recorder::default();
+        let mut audio = Audio::new(Box::new(recorder.clone()));
+        run(&mut audio);
+        recorder.events.take()
+    }
+
+    #[test]
+    fn plays_chime_for_each_rest_type() {
+        let settings = SoundSettings::default();
+        let events = recorded(|audio| {
+            audio.rest_started(&settings, Sound::Water);
+            audio.rest_ended(&settings);
+            audio.rest_started(&settings, Sound::Walk);
+        });
+        assert_eq!(
+            events,
+            [
+                Event::Play(Sound::Water, false),
+                Event::Play(Sound::RestEnd, false),
+                Event::Play(Sound::Walk, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn ambient_loops_until_rest_ends() {
+        let settings = SoundSettings {
+            ambient: true,
+            ..SoundSettings::default()
+        };
+        let events = recorded(|audio| {
+            audio.rest_started(&settings, Sound::RestStart);
+            // 连续开始两次休息时先停掉上一次的环境音
+            audio.rest_started(&settings, Sound::RestStart);
+            audio.rest_ended(&settings);
+            audio.stop_ambient();
+        });
+        assert_eq!(
+            events,
+            [
+                Event::Play(Sound::RestStart, false),
+                Event::Play(Sound::Ambient, true),
+                Event::Stop(Sound::Ambient),
+                Event::Play(Sound::RestStart, false),
+                Event::Play(Sound::Ambient, true),
+                Event::Stop(Sound::Ambient),
+                Event::Play(Sound::RestEnd, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn chimes_can_be_turned_off_separately() {
+        let settings = SoundSettings {
+            start_chime: false,
+            end_chime: false,
+            ambient: true,
+            ..SoundSettings::default()
+        };
+        let events = recorded(|audio| {
+            audio.rest_started(&settings, Sound::RestStart);
+            audio.rest_ended(&settings);
+        });
+        assert_eq!(
+            events,
+            [
+                Event::Play(Sound::Ambient, true),
+                Event::Stop(Sound::Ambient)
+            ]
+        );
+    }
+
+    #[test]
+    fn disabled_or_muted_plays_nothing() {
+        let enabled = SoundSettings {
+            ambient: true,
+            ..SoundSettings::default()
+        };
+        let disabled = SoundSettings {
+            enabled: false,
+            ..enabled.clone()
+        };
+        let muted = SoundSettings {
+            volume: 0.0,
+            ..enabled.clone()
+        };
+        for settings in [&disabled, &muted] {
+            let events = recorded(|audio| {
+                audio.rest_started(settings, Sound::RestStart);
+                audio.rest_ended(settings);
+            });
+            assert_eq!(events, []);
+        }
+
+        // 休息中关掉声音，已经在放的环境音也要停
+        let events = recorded(|audio| {
+            audio.rest_started(&enabled, Sound::Water);
+            audio.rest_started(&disabled, Sound::Water);
+        });
+        assert_eq!(events.last(), Some(&Event::Stop(Sound::Ambient)));
+    }
+
+    #[test]
+    fn null_audio_tracks_ambient() {
+        let settings = SoundSettings {
+            ambient: true,
+            ..SoundSettings::default()
+        };
+        let mut audio = Audio::new(Box::new(NullAudio));
+        audio.rest_started(&settings, Sound::RestStart);
+        assert!(audio.ambient.is_some());
+        audio.rest_ended(&settings);
+        assert!(audio.ambient.is_none());
+    }
+
+    /// 单声道 16 位 PCM，`samples` 个采样，每秒 `rate` 个
+    fn pcm16(rate: u32, samples: &[i16]) -> Vec<u8> {
+        let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data.len() as u32).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&rate.to_le_bytes());
+        wav.extend_from_slice(&(rate * 2).to_le_bytes());
+        wav.extend_from_slice(&2u16.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        wav.extend_from_slice(&data);
+        wav
+    }
+
+    #[test]
+    fn scales_pcm16_samples() {
+        let mut wav = pcm16(8000, &[1000, -2000]);
+        scale_volume(&mut wav, 0.5);
+        assert_eq!(wav, pcm16(8000, &[500, -1000]));
+    }
+
+    #[test]
+    fn leaves_other_data_alone() {
+        let mut data = b"not a wave file".to_vec();
+        scale_volume(&mut data, 0.5);
+        assert_eq!(data, b"not a wave file");
+    }
+
+    #[test]
+    fn measures_duration() {
+        let wav = pcm16(8000, &[0; 4000]);
+        assert_eq!(wav_duration(&wav), Some(Duration::from_millis(500)));
+        assert_eq!(wav_duration(b"RIFF"), None);
+        let bundled = wav_duration(Sound::RestStart.bundled()).unwrap();
+        assert!(bundled > Duration::ZERO && bundled < Duration::from_secs(10));
+    }
+}
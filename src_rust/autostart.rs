@@ -0,0 +1,279 @@
+//! 登录时启动：Linux 在 XDG autostart 目录放一个 `.desktop` 文件，Windows 写入注册表的 Run 键
+//!
+//! 是否已开启以系统中的记录为准，不另存到配置文件，用户在系统设置里删掉也能正确显示。
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// `.desktop` 文件名与注册表中的值名
+const APP_ID: &str = "are_you_blind";
+
+/// 登录时启动的程序：AppImage 运行时 `current_exe` 指向临时挂载点，改用 `$APPIMAGE`
+fn executable() -> io::Result<PathBuf> {
+    match std::env::var_os("APPIMAGE") {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => std::env::current_exe(),
+    }
+}
+
+/// 当前是否已设置登录时启动
+pub fn is_enabled() -> bool {
+    #[cfg(target_os = "windows")]
+    return windows::is_enabled();
+    #[cfg(not(target_os = "windows"))]
+    autostart_dir().is_some_and(|dir| is_installed(&dir))
+}
+
+/// 开启或关闭登录时启动
+pub fn set_enabled(enabled: bool) -> io::Result<()> {
+    #[cfg(target_os = "windows")]
+    return windows::set_enabled(enabled);
+    #[cfg(not(target_os = "windows"))]
+    {
+        let dir = autostart_dir()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+        if enabled {
+            install(&dir, &executable()?).map(|_| ())
+        } else {
+            uninstall(&dir)
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/autostart`，未设置时为 `~/.config/autostart`
+pub fn autostart_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("autostart"))
+}
+
+/// `dir` 中的 `.desktop` 文件路径
+pub fn desktop_file(dir: &Path) -> PathBuf {
+    dir.join(format!("{}.desktop", APP_ID))
+}
+
+/// 生成 autostart 用的桌面项，`exec` 按桌面项规范加引号转义
+pub fn desktop_entry(exec: &Path) -> String {
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=瞎了么\n\
+         Comment=定时提醒休息眼睛\n\
+         Exec={}\n\
+         Terminal=false\n\
+         X-GNOME-Autostart-enabled=true\n",
+        quote_exec(&exec.to_string_lossy())
+    )
+}
+
+/// Exec 参数中需要加引号的保留字符（空白另外判断）
+const RESERVED: &str = "\"'\\><~|&;$*?#()`";
+
+/// Exec 键的写法：含空格等保留字符时加双引号，引号内转义 `"`、`` ` ``、`$`、`\`；
+/// `%` 是字段代码的前缀，需要写成 `%%`
+fn quote_exec(arg: &str) -> String {
+    let arg = arg.replace('%', "%%");
+    let reserved = |c: char| c.is_whitespace() || RESERVED.contains(c);
+    if !arg.contains(reserved) {
+        return arg;
+    }
+    let mut quoted = String::from('"');
+    for c in arg.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    // 桌面项的字符串值本身还要再转义一次反斜杠
+    quoted.replace('\\', "\\\\")
+}
+
+/// 在 `dir` 中写入桌面项，目录不存在时创建
+pub fn install(dir: &Path, exec: &Path) -> io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let path = desktop_file(dir);
+    std::fs::write(&path, desktop_entry(exec))?;
+    Ok(path)
+}
+
+/// 删除桌面项；本来就不存在时视为成功
+pub fn uninstall(dir: &Path) -> io::Result<()> {
+    match std::fs::remove_file(desktop_file(dir)) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// 桌面项存在且没有被 `Hidden=true` 禁用
+pub fn is_installed(dir: &Path) -> bool {
+    std::fs::read_to_string(desktop_file(dir))
+        .is_ok_and(|text| !text.lines().any(|line| line.trim() == "Hidden=true"))
+}
+
+#[cfg(target_os = "windows")]
+mod windows {
+    #![allow(clippy::upper_case_acronyms)]
+
+    use super::{executable, APP_ID};
+    use std::io;
+
+    type HKEY = isize;
+    type LSTATUS = i32;
+
+    const HKEY_CURRENT_USER: HKEY = 0x8000_0001_u32 as i32 as isize;
+    const REG_SZ: u32 = 1;
+    const RRF_RT_REG_SZ: u32 = 0x0000_0002;
+    const ERROR_FILE_NOT_FOUND: LSTATUS = 2;
+    const RUN_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Run";
+
+    #[link(name = "advapi32")]
+    extern "system" {
+        fn RegGetValueW(
+            key: HKEY,
+            subkey: *const u16,
+            value: *const u16,
+            flags: u32,
+            kind: *mut u32,
+            data: *mut u8,
+            size: *mut u32,
+        ) -> LSTATUS;
+        fn RegSetKeyValueW(
+            key: HKEY,
+            subkey: *const u16,
+            value: *const u16,
+            kind: u32,
+            data: *const u8,
+            size: u32,
+        ) -> LSTATUS;
+        fn RegDeleteKeyValueW(key: HKEY, subkey: *const u16, value: *const u16) -> LSTATUS;
+    }
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    pub fn is_enabled() -> bool {
+        let (subkey, value) = (wide(RUN_KEY), wide(APP_ID));
+        let status = unsafe {
+            RegGetValueW(
+                HKEY_CURRENT_USER,
+                subkey.as_ptr(),
+                value.as_ptr(),
+                RRF_RT_REG_SZ,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            )
+        };
+        status == 0
+    }
+
+    pub fn set_enabled(enabled: bool) -> io::Result<()> {
+        let (subkey, value) = (wide(RUN_KEY), wide(APP_ID));
+        let status = if enabled {
+            // 路径可能含空格，整体加引号
+            let command = wide(&format!("\"{}\"", executable()?.display()));
+            unsafe {
+                RegSetKeyValueW(
+                    HKEY_CURRENT_USER,
+                    subkey.as_ptr(),
+                    value.as_ptr(),
+                    REG_SZ,
+                    command.as_ptr() as *const u8,
+                    (command.len() * 2) as u32,
+                )
+            }
+        } else {
+            match unsafe { RegDeleteKeyValueW(HKEY_CURRENT_USER, subkey.as_ptr(), value.as_ptr()) }
+            {
+                ERROR_FILE_NOT_FOUND => 0,
+                status => status,
+            }
+        };
+        if status == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::from_raw_os_error(status))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试专用的临时目录，结束时删除
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("are_you_blind_{}_{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn install_and_uninstall_round_trip() {
+        let temp = TempDir::new("autostart");
+        let dir = temp.0.join("config").join("autostart");
+        let exec = Path::new("/opt/are you blind/are_you_blind");
+        assert!(!is_installed(&dir));
+
+        let path = install(&dir, exec).unwrap();
+        assert_eq!(path, desktop_file(&dir));
+        assert_eq!(path.file_name().unwrap(), "are_you_blind.desktop");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), desktop_entry(exec));
+        assert!(is_installed(&dir));
+
+        uninstall(&dir).unwrap();
+        assert!(!is_installed(&dir));
+        // 再次删除不存在的文件不算错误
+        uninstall(&dir).unwrap();
+    }
+
+    #[test]
+    fn hidden_entry_counts_as_disabled() {
+        let temp = TempDir::new("autostart_hidden");
+        let path = install(&temp.0, Path::new("/usr/bin/are_you_blind")).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, text + "Hidden=true\n").unwrap();
+        assert!(!is_installed(&temp.0));
+    }
+
+    #[test]
+    fn desktop_entry_has_exec_line() {
+        let entry = desktop_entry(Path::new("/usr/bin/are_you_blind"));
+        assert!(entry.starts_with("[Desktop Entry]\n"));
+        assert!(entry.lines().any(|line| line == "Type=Application"));
+        assert!(entry
+            .lines()
+            .any(|line| line == "Exec=/usr/bin/are_you_blind"));
+    }
+
+    #[test]
+    fn quote_exec_leaves_plain_paths() {
+        assert_eq!(
+            quote_exec("/usr/bin/are_you_blind"),
+            "/usr/bin/are_you_blind"
+        );
+        assert_eq!(quote_exec("/opt/100%/app"), "/opt/100%%/app");
+    }
+
+    #[test]
+    fn quote_exec_quotes_reserved_characters() {
+        assert_eq!(quote_exec("/opt/my app/run"), "\"/opt/my app/run\"");
+        assert_eq!(quote_exec("/opt/a&b"), "\"/opt/a&b\"");
+        // 引号内转义后，字符串值再把反斜杠写成两个
+        assert_eq!(quote_exec("/opt/$HOME app"), r#""/opt/\\$HOME app""#);
+        assert_eq!(quote_exec("/opt/say \"hi\""), r#""/opt/say \\"hi\\"""#);
+        assert_eq!(quote_exec("/opt/a`b"), r#""/opt/a\\`b""#);
+        assert_eq!(quote_exec(r"/opt/a\b"), r#""/opt/a\\\\b""#);
+    }
+}
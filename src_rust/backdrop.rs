@@ -0,0 +1,99 @@
+//! 休息遮罩的背景图片：从图片目录按休息轮换，并按每块显示器缩放裁剪
+
+use image::{imageops, RgbaImage};
+use rand::seq::SliceRandom;
+use slint::{Image, Rgba8Pixel, SharedPixelBuffer};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// 图片目录中会被使用的文件类型
+const EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+
+/// 图片目录的轮换：打乱顺序后每次休息取一张，用完一轮再重新扫描目录
+#[derive(Default)]
+pub struct Slideshow {
+    dir: Option<PathBuf>,
+    queue: Vec<PathBuf>,
+}
+
+impl Slideshow {
+    /// 下一次休息的背景；目录为空或图片都无法解码时返回 None
+    pub fn next(&mut self, dir: &Path) -> Option<Rc<RgbaImage>> {
+        if self.dir.as_deref() != Some(dir) {
+            self.dir = Some(dir.to_path_buf());
+            self.queue.clear();
+        }
+
+        let mut rescanned = false;
+        loop {
+            if self.queue.is_empty() {
+                if rescanned {
+                    return None;
+                }
+                self.queue = scan_images(dir);
+                self.queue.shuffle(&mut rand::thread_rng());
+                rescanned = true;
+            }
+            // 无法解码的文件直接跳过
+            if let Some(image) = self.queue.pop().and_then(|path| load(&path)) {
+                return Some(image);
+            }
+        }
+    }
+}
+
+fn scan_images(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| EXTENSIONS.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+        })
+        .collect()
+}
+
+/// 解码单张图片
+pub fn load(path: &Path) -> Option<Rc<RgbaImage>> {
+    image::open(path)
+        .ok()
+        .map(|image| Rc::new(image.to_rgba8()))
+}
+
+/// 等比缩放到刚好铺满 `width` x `height`，居中裁掉多余部分
+pub fn fit_cover(source: &RgbaImage, width: u32, height: u32) -> RgbaImage {
+    let (src_width, src_height) = source.dimensions();
+    if width == 0 || height == 0 || src_width == 0 || src_height == 0 {
+        return source.clone();
+    }
+
+    let scale = (width as f32 / src_width as f32).max(height as f32 / src_height as f32);
+    let scaled_width = ((src_width as f32 * scale).ceil() as u32).max(width);
+    let scaled_height = ((src_height as f32 * scale).ceil() as u32).max(height);
+    let mut scaled = imageops::resize(
+        source,
+        scaled_width,
+        scaled_height,
+        imageops::FilterType::Triangle,
+    );
+    imageops::crop(
+        &mut scaled,
+        (scaled_width - width) / 2,
+        (scaled_height - height) / 2,
+        width,
+        height,
+    )
+    .to_image()
+}
+
+pub fn to_slint_image(image: &RgbaImage) -> Image {
+    let buffer = SharedPixelBuffer::<Rgba8Pixel>::clone_from_slice(
+        image.as_raw(),
+        image.width(),
+        image.height(),
+    );
+    Image::from_rgba8(buffer)
+}
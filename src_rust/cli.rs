@@ -0,0 +1,145 @@
+//! 命令行参数：本次运行的初始状态
+//!
+//! `--work`、`--rest`、`--start-paused`、`--minimized` 只影响这一次运行，不写入配置文件，
+//! 运行中切换方案（包括自动切换规则）后指定的时长仍然生效；
+//! `--profile` 与在托盘中切换方案一样会被记住。
+
+use crate::duration_format::{
+    format_duration, parse_duration, MAX_WORK_DURATION, MIN_WORK_DURATION,
+};
+use std::path::PathBuf;
+use std::time::Duration;
+
+const USAGE: &str = "\
+Usage: are_you_blind [OPTIONS]
+
+Options:
+  --work <DURATION>   Work duration for this run, e.g. 25m or 1h30m
+  --rest <DURATION>   Rest duration for this run, 5s to 5m, e.g. 30s
+  --profile <NAME>    Switch to the named profile
+  --start-paused      Start with the timer paused
+  --minimized         Start hidden in the tray (minimized with --no-tray)
+  --config <PATH>     Read and write settings at PATH instead of the default location
+  --no-tray           Do not create a tray icon; hiding the window minimizes it instead
+  --headless          Run without any windows or tray icon; reminders are sent as desktop
+                      notifications and commands (pause, resume, skip, rest, status,
+                      profile, quit) are read line by line from standard input
+  -h, --help          Print this help";
+
+/// 休息时长允许的范围，与设置面板一致
+const MIN_REST_DURATION: Duration = Duration::from_secs(5);
+const MAX_REST_DURATION: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Default)]
+pub struct Options {
+    pub work: Option<Duration>,
+    pub rest: Option<Duration>,
+    pub profile: Option<String>,
+    pub start_paused: bool,
+    pub minimized: bool,
+    pub config: Option<PathBuf>,
+    pub no_tray: bool,
+    pub headless: bool,
+}
+
+impl Options {
+    /// 解析进程参数；`--help` 打印用法后退出，参数有误时打印原因并以状态码 2 退出
+    pub fn from_env() -> Self {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        if !args.is_empty() {
+            attach_console();
+        }
+        match Self::parse(args) {
+            Ok(Some(options)) => options,
+            Ok(None) => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            Err(message) => {
+                eprintln!("{}\n\n{}", message, USAGE);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    /// 支持 `--work 25m` 与 `--work=25m` 两种写法；遇到 `--help` 时返回 None
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut options = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (name, inline) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => (name.to_string(), Some(value)),
+                _ => (arg.clone(), None),
+            };
+            let mut value = || {
+                inline
+                    .map(str::to_string)
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("Missing value for {}", name))
+            };
+            match name.as_str() {
+                "--work" => {
+                    let work = duration(&value()?, &name)?;
+                    options.work = Some(within(work, MIN_WORK_DURATION, MAX_WORK_DURATION, &name)?);
+                }
+                "--rest" => {
+                    let rest = duration(&value()?, &name)?;
+                    options.rest = Some(within(rest, MIN_REST_DURATION, MAX_REST_DURATION, &name)?);
+                }
+                "--profile" => options.profile = Some(value()?),
+                "--config" => options.config = Some(PathBuf::from(value()?)),
+                "--start-paused" => options.start_paused = true,
+                "--minimized" => options.minimized = true,
+                "--no-tray" => options.no_tray = true,
+                "--headless" => options.headless = true,
+                "-h" | "--help" => return Ok(None),
+                other => return Err(format!("Unknown argument: {}", other)),
+            }
+        }
+        Ok(Some(options))
+    }
+
+    /// 是否创建托盘图标
+    pub fn tray(&self) -> bool {
+        !self.no_tray && !self.headless
+    }
+}
+
+fn duration(text: &str, name: &str) -> Result<Duration, String> {
+    parse_duration(text).ok_or_else(|| format!("Invalid duration for {}: {}", name, text))
+}
+
+fn within(
+    duration: Duration,
+    min: Duration,
+    max: Duration,
+    name: &str,
+) -> Result<Duration, String> {
+    if (min..=max).contains(&duration) {
+        Ok(duration)
+    } else {
+        Err(format!(
+            "{} must be between {} and {}",
+            name,
+            format_duration(min),
+            format_duration(max)
+        ))
+    }
+}
+
+/// Windows 下程序没有控制台窗口，从终端启动时接到父进程的控制台上，
+/// 这样 `--help`、参数错误与无窗口模式的输出才能看到
+#[cfg(target_os = "windows")]
+fn attach_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn attach_console() {}
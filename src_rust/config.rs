@@ -0,0 +1,813 @@
+//! 配置持久化：设置方案（Profile）、按时间自动切换的规则，以及托盘、主题、遮罩、声音、
+//! 快捷键、钩子、HTTP 接口、窗口位置等各部分的设置
+//!
+//! 配置文件位于 `<config_dir>/are_you_blind/config.toml`，缺失时使用默认值；无法解析时改名为
+//! `config.toml.bak` 保留下来再使用默认值，避免之后的自动保存覆盖手写的内容。
+
+use crate::duration_format::{MAX_WORK_DURATION, MIN_WORK_DURATION};
+use chrono::{Datelike, Local, Timelike};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// 休息的强制程度
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strictness {
+    /// 遮罩上提供「跳过」按钮
+    Relaxed,
+    /// 只能通过主窗口跳过
+    #[default]
+    Normal,
+    /// 休息期间不允许跳过
+    Strict,
+}
+
+/// 休息提示文案的风格
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessagePack {
+    /// 全部文案随机
+    #[default]
+    Mixed,
+    /// 科普、健康知识
+    Science,
+    /// 幽默、激励
+    Humor,
+    /// 只显示简洁的固定文案
+    Plain,
+}
+
+/// 托盘图标显示剩余时间的方式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayIconStyle {
+    /// Logo 外圈的进度圆环
+    #[default]
+    Arc,
+    /// 剩余分钟数
+    Digits,
+    /// 固定的 Logo
+    Static,
+}
+
+/// 休息遮罩的背景样式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlayStyle {
+    /// 纯黑
+    #[default]
+    Solid,
+    /// 半透明调暗，仍能看到下面的窗口
+    Dim,
+    /// 用户图片
+    Image,
+    /// 渐变
+    Gradient,
+}
+
+impl OverlayStyle {
+    /// 设置面板中的顺序，与 `MainWindow.overlay-style` 的取值对应
+    pub const ALL: [OverlayStyle; 4] = [
+        OverlayStyle::Solid,
+        OverlayStyle::Dim,
+        OverlayStyle::Image,
+        OverlayStyle::Gradient,
+    ];
+
+    pub fn index(self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|style| *style == self)
+            .unwrap_or(0)
+    }
+
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL.get(index).copied().unwrap_or_default()
+    }
+}
+
+/// 休息遮罩外观
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverlaySettings {
+    pub style: OverlayStyle,
+    /// `dim` 样式的不透明度（0.2 – 1.0）
+    pub opacity: f32,
+    /// `image` 样式的背景图片
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<PathBuf>,
+    /// `image` 样式的图片目录，每次休息轮换一张；设置后优先于 `image`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_dir: Option<PathBuf>,
+    /// `gradient` 样式的起止颜色，"#rrggbb"
+    pub gradient: [String; 2],
+}
+
+impl Default for OverlaySettings {
+    fn default() -> Self {
+        Self {
+            style: OverlayStyle::default(),
+            opacity: 0.85,
+            image: None,
+            image_dir: None,
+            gradient: ["#0f172a".into(), "#134e4a".into()],
+        }
+    }
+}
+
+/// 界面主题
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeMode {
+    /// 跟随系统的浅色/深色设置
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl ThemeMode {
+    /// 设置面板中的顺序，与 `AppPalette.theme-mode` 的取值对应
+    pub const ALL: [ThemeMode; 3] = [ThemeMode::System, ThemeMode::Light, ThemeMode::Dark];
+
+    pub fn index(self) -> usize {
+        Self::ALL.iter().position(|mode| *mode == self).unwrap_or(0)
+    }
+
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL.get(index).copied().unwrap_or_default()
+    }
+}
+
+/// 覆盖一套内置配色中的颜色，"#rrggbb"；没写的保持默认
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PaletteColors {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_primary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_secondary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub border: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub surface_1: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub surface_2: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ring_track: Option<String>,
+}
+
+impl PaletteColors {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// 主题模式与自定义配色
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeSettings {
+    pub mode: ThemeMode,
+    /// 强调色，浅色和深色共用
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accent: Option<String>,
+    #[serde(skip_serializing_if = "PaletteColors::is_empty")]
+    pub light: PaletteColors,
+    #[serde(skip_serializing_if = "PaletteColors::is_empty")]
+    pub dark: PaletteColors,
+}
+
+/// 无障碍：高对比度配色与文字缩放
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    pub high_contrast: bool,
+    /// 小号文字的缩放比例，1.0–1.5；大号倒计时不缩放
+    pub text_scale: f32,
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        Self {
+            high_contrast: false,
+            text_scale: 1.0,
+        }
+    }
+}
+
+/// 解析 "#rrggbb" 颜色
+pub fn parse_hex_color(s: &str) -> Option<[u8; 3]> {
+    let hex = s.trim().strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    Some([(value >> 16) as u8, (value >> 8) as u8, value as u8])
+}
+
+/// 一组可整体切换的设置，例如「编程」「会议」「游戏」
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
+    #[serde(alias = "work_minutes", with = "work_duration")]
+    pub work: Duration,
+    pub rest_seconds: u32,
+    pub water_enabled: bool,
+    pub water_interval: u32, // 每几轮护眼提醒后触发喝水提醒
+    pub walk_enabled: bool,
+    pub walk_interval: u32, // 每几轮护眼提醒后触发走动提醒
+    pub strictness: Strictness,
+    pub message_pack: MessagePack,
+    /// 休息结束后等检测到键盘或鼠标操作再开始下一轮专注，离开的时间不计入工作
+    pub await_return: bool,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            name: "编程".into(),
+            work: Duration::from_secs(20 * 60),
+            rest_seconds: 20,
+            water_enabled: true,
+            water_interval: 2,
+            walk_enabled: true,
+            walk_interval: 3,
+            strictness: Strictness::Normal,
+            message_pack: MessagePack::Mixed,
+            await_return: false,
+        }
+    }
+}
+
+impl Profile {
+    /// 把数值限制在设置面板允许的范围内
+    fn normalize(&mut self) {
+        self.work = self.work.clamp(MIN_WORK_DURATION, MAX_WORK_DURATION);
+        self.rest_seconds = self.rest_seconds.clamp(5, 300);
+        self.water_interval = self.water_interval.clamp(1, 20);
+        self.walk_interval = self.walk_interval.clamp(1, 20);
+    }
+}
+
+/// 工作时长在配置文件中写作 "20m"、"1h30m" 等文本，纯数字按分钟处理
+mod work_duration {
+    use crate::duration_format::{format_duration, parse_duration};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Text(String),
+        Minutes(u64),
+    }
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format_duration(*duration))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        match Raw::deserialize(deserializer)? {
+            Raw::Text(text) => parse_duration(&text)
+                .ok_or_else(|| D::Error::custom(format!("invalid duration: {:?}", text))),
+            Raw::Minutes(minutes) => minutes
+                .checked_mul(60)
+                .map(Duration::from_secs)
+                .ok_or_else(|| D::Error::custom(format!("duration too long: {} minutes", minutes))),
+        }
+    }
+}
+
+/// 在指定星期和时间段内自动切换到某个方案
+///
+/// `days` 使用 1 = 周一 … 7 = 周日，留空表示每天；`start`/`end` 为 "HH:MM"，
+/// `end` 早于 `start` 时表示跨越午夜。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleRule {
+    pub profile: String,
+    #[serde(default)]
+    pub days: Vec<u8>,
+    pub start: String,
+    pub end: String,
+}
+
+impl ScheduleRule {
+    fn matches(&self, weekday: u8, minute_of_day: u32) -> bool {
+        let (Some(start), Some(end)) = (parse_hh_mm(&self.start), parse_hh_mm(&self.end)) else {
+            return false;
+        };
+
+        if start <= end {
+            self.includes_day(weekday) && minute_of_day >= start && minute_of_day < end
+        } else if minute_of_day >= start {
+            self.includes_day(weekday)
+        } else {
+            // 跨午夜的后半段属于前一天的规则
+            let yesterday = if weekday == 1 { 7 } else { weekday - 1 };
+            minute_of_day < end && self.includes_day(yesterday)
+        }
+    }
+
+    fn includes_day(&self, weekday: u8) -> bool {
+        self.days.is_empty() || self.days.contains(&weekday)
+    }
+}
+
+fn parse_hh_mm(s: &str) -> Option<u32> {
+    let (h, m) = s.trim().split_once(':')?;
+    let (h, m): (u32, u32) = (h.parse().ok()?, m.parse().ok()?);
+    (h < 24 && m < 60).then_some(h * 60 + m)
+}
+
+/// 提示音与环境音
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundSettings {
+    pub enabled: bool,
+    /// 音量（0.0 – 1.0）
+    pub volume: f32,
+    /// 休息开始时按提醒类型播放提示音
+    pub start_chime: bool,
+    pub end_chime: bool,
+    /// 休息期间循环播放环境音
+    pub ambient: bool,
+    /// 用自己的 WAV 替换内置声音，键为 rest_start / water / walk / rest_end / ambient
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub files: BTreeMap<String, PathBuf>,
+}
+
+impl Default for SoundSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            volume: 0.6,
+            start_chime: true,
+            end_chime: true,
+            ambient: false,
+            files: BTreeMap::new(),
+        }
+    }
+}
+
+/// 快捷键，写作 "Space"、"S"、","、"Escape"、"Ctrl+Alt+P"；留空表示不使用
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    /// 主窗口：暂停 / 继续
+    pub pause: String,
+    /// 主窗口：重新计时；遮罩：跳过休息（仅宽松模式）
+    pub skip: String,
+    /// 主窗口：打开设置
+    pub settings: String,
+    /// 主窗口：隐藏到托盘
+    pub hide: String,
+    /// 遮罩：推迟这次休息（严格模式不可用）
+    pub postpone: String,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            pause: "Space".into(),
+            skip: "S".into(),
+            settings: ",".into(),
+            hide: "Escape".into(),
+            postpone: "P".into(),
+        }
+    }
+}
+
+/// 全局快捷键，在其他程序中也能触发；写法同 `KeyBindings`，留空表示不注册
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HotkeySettings {
+    /// 暂停 / 继续
+    pub pause: String,
+    /// 立即休息
+    pub rest_now: String,
+    /// 跳过下一次休息
+    pub skip_next: String,
+    /// 显示主窗口
+    pub show_window: String,
+}
+
+impl Default for HotkeySettings {
+    fn default() -> Self {
+        Self {
+            pause: "Ctrl+Alt+P".into(),
+            rest_now: "Ctrl+Alt+R".into(),
+            skip_next: "Ctrl+Alt+S".into(),
+            show_window: "Ctrl+Alt+E".into(),
+        }
+    }
+}
+
+/// 事件钩子：计时器状态变化时交给 shell 执行的命令，留空表示不执行
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HookSettings {
+    /// 命令运行超过这么多秒就结束它
+    pub timeout_seconds: u64,
+    /// 工作结束前多少秒触发 `warning`，0 表示不触发
+    pub warning_seconds: u64,
+    pub work_started: String,
+    pub warning: String,
+    pub rest_started: String,
+    pub rest_ended: String,
+    /// 休息被跳过或推迟
+    pub rest_skipped: String,
+    pub paused: String,
+    pub resumed: String,
+}
+
+impl Default for HookSettings {
+    fn default() -> Self {
+        Self {
+            timeout_seconds: 10,
+            warning_seconds: 60,
+            work_started: String::new(),
+            warning: String::new(),
+            rest_started: String::new(),
+            rest_ended: String::new(),
+            rest_skipped: String::new(),
+            paused: String::new(),
+            resumed: String::new(),
+        }
+    }
+}
+
+/// 本机 HTTP 接口，只监听 127.0.0.1
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 7463,
+        }
+    }
+}
+
+/// 主窗口的位置（虚拟桌面中的物理像素）与是否可见
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WindowPlacement {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    /// 退出时是否显示，隐藏到托盘时为 false
+    pub visible: bool,
+    /// 迷你模式小窗的左上角
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compact: Option<[i32; 2]>,
+}
+
+/// 迷你模式：只显示倒计时和进度条的置顶小窗
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompactSettings {
+    /// 退出时是否处于迷你模式，下次启动时恢复
+    pub enabled: bool,
+    /// 鼠标不在小窗上时的不透明度，0.2–1.0
+    pub opacity: f32,
+    /// 拖到离屏幕边缘多近时贴边（逻辑像素），0 表示不吸附
+    pub snap_distance: u32,
+    /// 鼠标没有停留在小窗上时点击穿透到下面的窗口（Wayland 不支持）
+    pub click_through: bool,
+}
+
+impl Default for CompactSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            opacity: 0.7,
+            snap_distance: 24,
+            click_through: true,
+        }
+    }
+}
+
+/// 主窗口的启动方式与位置记忆
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowSettings {
+    /// 启动时隐藏到托盘，不管上次退出时窗口是否可见
+    pub start_minimized: bool,
+    /// 最近一次保存位置时的显示器布局，遇到没见过的布局时以它为准
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_layout: Option<String>,
+    /// 按显示器布局（见 `window_placement::layout_key`）分别保存的位置
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub placements: BTreeMap<String, WindowPlacement>,
+}
+
+impl WindowSettings {
+    /// 当前布局的位置；没有记录时取最近一次保存的位置
+    pub fn placement(&self, layout: &str) -> Option<&WindowPlacement> {
+        self.placements.get(layout).or_else(|| {
+            self.last_layout
+                .as_ref()
+                .and_then(|last| self.placements.get(last))
+        })
+    }
+}
+
+/// 单个显示器的设置，按 `dump_monitors` 显示的输出名配置
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonitorSettings {
+    /// 休息时不覆盖这块屏幕，例如竖放的状态屏或电视镜像
+    pub skip: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub active_profile: String,
+    pub profiles: Vec<Profile>,
+    pub schedule: Vec<ScheduleRule>,
+    pub tray_icon: TrayIconStyle,
+    pub theme: ThemeSettings,
+    pub accessibility: AccessibilitySettings,
+    pub overlay: OverlaySettings,
+    pub sound: SoundSettings,
+    pub keys: KeyBindings,
+    pub hotkeys: HotkeySettings,
+    pub hooks: HookSettings,
+    pub http: HttpSettings,
+    pub window: WindowSettings,
+    pub compact: CompactSettings,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub monitors: BTreeMap<String, MonitorSettings>,
+    /// 自动切换规则生效前用户自己选的方案；规则切换的方案只在内存中，保存时写入这一个
+    #[serde(skip)]
+    pub profile_before_schedule: Option<String>,
+    /// 配置文件无法解析、也没能改名保留时不再写回，等用户修好文件
+    #[serde(skip)]
+    keep_broken_file: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let coding = Profile::default();
+        let meetings = Profile {
+            name: "会议".into(),
+            work: Duration::from_secs(45 * 60),
+            rest_seconds: 60,
+            water_interval: 1,
+            walk_interval: 1,
+            strictness: Strictness::Relaxed,
+            message_pack: MessagePack::Plain,
+            ..Profile::default()
+        };
+        let gaming = Profile {
+            name: "游戏".into(),
+            work: Duration::from_secs(30 * 60),
+            walk_interval: 2,
+            strictness: Strictness::Relaxed,
+            message_pack: MessagePack::Humor,
+            ..Profile::default()
+        };
+
+        Self {
+            active_profile: coding.name.clone(),
+            profiles: vec![coding, meetings, gaming],
+            schedule: Vec::new(),
+            tray_icon: TrayIconStyle::default(),
+            theme: ThemeSettings::default(),
+            accessibility: AccessibilitySettings::default(),
+            overlay: OverlaySettings::default(),
+            sound: SoundSettings::default(),
+            keys: KeyBindings::default(),
+            hotkeys: HotkeySettings::default(),
+            hooks: HookSettings::default(),
+            http: HttpSettings::default(),
+            window: WindowSettings::default(),
+            compact: CompactSettings::default(),
+            monitors: BTreeMap::new(),
+            profile_before_schedule: None,
+            keep_broken_file: false,
+        }
+    }
+}
+
+/// 命令行 `--config` 指定的配置文件
+static PATH_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+impl Config {
+    /// 改用指定的配置文件读写设置，需在 `load` 之前调用
+    pub fn use_path(path: PathBuf) {
+        let _ = PATH_OVERRIDE.set(path);
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = PATH_OVERRIDE.get() {
+            return Some(path.clone());
+        }
+        dirs::config_dir().map(|dir| dir.join("are_you_blind").join("config.toml"))
+    }
+
+    /// 读取配置文件；文件不存在或无法解析时返回默认配置
+    pub fn load() -> Self {
+        let mut config = Self::path()
+            .map(|path| Self::read(&path))
+            .unwrap_or_default();
+        config.normalize();
+        config
+    }
+
+    /// 读取指定文件；无法解析时改名为 `<文件名>.bak`，改名失败时之后不再保存
+    fn read(path: &Path) -> Self {
+        let Ok(text) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        let e = match toml::from_str::<Config>(&text) {
+            Ok(config) => return config,
+            Err(e) => e,
+        };
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(".bak");
+        let backup = PathBuf::from(backup);
+        match std::fs::rename(path, &backup) {
+            Ok(()) => {
+                eprintln!(
+                    "Failed to parse config, moved it to {} and using defaults: {}",
+                    backup.display(),
+                    e
+                );
+                Self::default()
+            }
+            Err(rename_error) => {
+                eprintln!(
+                    "Failed to parse config, using defaults and not saving until it is fixed: {} \
+                     (could not move it aside: {})",
+                    e, rename_error
+                );
+                Self {
+                    keep_broken_file: true,
+                    ..Self::default()
+                }
+            }
+        }
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        if self.keep_broken_file {
+            return Err(std::io::Error::other(
+                "config file could not be parsed, fix it before saving",
+            ));
+        }
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, self.to_toml()?)
+    }
+
+    fn to_toml(&self) -> std::io::Result<String> {
+        let text = match &self.profile_before_schedule {
+            Some(profile) => toml::to_string_pretty(&Config {
+                active_profile: profile.clone(),
+                ..self.clone()
+            }),
+            None => toml::to_string_pretty(self),
+        };
+        text.map_err(std::io::Error::other)
+    }
+
+    fn normalize(&mut self) {
+        if self.profiles.is_empty() {
+            self.profiles.push(Profile::default());
+        }
+        for profile in &mut self.profiles {
+            profile.normalize();
+        }
+        if self.profile_index(&self.active_profile).is_none() {
+            self.active_profile = self.profiles[0].name.clone();
+        }
+        self.accessibility.text_scale = self.accessibility.text_scale.clamp(1.0, 1.5);
+        self.compact.opacity = self.compact.opacity.clamp(0.2, 1.0);
+        self.hooks.timeout_seconds = self.hooks.timeout_seconds.max(1);
+    }
+
+    pub fn profile_index(&self, name: &str) -> Option<usize> {
+        self.profiles.iter().position(|p| p.name == name)
+    }
+
+    pub fn active_index(&self) -> usize {
+        self.profile_index(&self.active_profile).unwrap_or(0)
+    }
+
+    pub fn active(&self) -> &Profile {
+        &self.profiles[self.active_index()]
+    }
+
+    pub fn active_mut(&mut self) -> &mut Profile {
+        let index = self.active_index();
+        &mut self.profiles[index]
+    }
+
+    /// 休息时不覆盖的输出名
+    pub fn skipped_outputs(&self) -> Vec<String> {
+        self.monitors
+            .iter()
+            .filter(|(_, settings)| settings.skip)
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// 当前时间命中的第一条自动切换规则
+    pub fn current_schedule_rule(&self) -> Option<usize> {
+        let now = Local::now();
+        let weekday = now.weekday().number_from_monday() as u8;
+        let minute_of_day = now.hour() * 60 + now.minute();
+        self.schedule
+            .iter()
+            .position(|rule| rule.matches(weekday, minute_of_day))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn work_duration_accepts_text_and_minutes() {
+        let profile: Profile = toml::from_str("work = \"1h30m\"").unwrap();
+        assert_eq!(profile.work, Duration::from_secs(5400));
+        let profile: Profile = toml::from_str("work_minutes = 25").unwrap();
+        assert_eq!(profile.work, Duration::from_secs(1500));
+    }
+
+    #[test]
+    fn work_duration_rejects_overflow() {
+        let minutes = u64::MAX / 60 + 1;
+        assert!(toml::from_str::<Profile>(&format!("work = {}", minutes)).is_err());
+        assert!(toml::from_str::<Profile>(&format!("work = \"{}\"", minutes)).is_err());
+    }
+
+    /// 测试专用的临时目录，结束时删除
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("are_you_blind_{}_{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn broken_file_is_moved_aside() {
+        let temp = TempDir::new("config");
+        let path = temp.0.join("config.toml");
+        let text = "active_profile = \"会议\"\n[[profiles]\n";
+        std::fs::write(&path, text).unwrap();
+
+        let config = Config::read(&path);
+        assert_eq!(config, Config::default());
+        assert!(!path.exists());
+        let backup = temp.0.join("config.toml.bak");
+        assert_eq!(std::fs::read_to_string(backup).unwrap(), text);
+
+        // 文件不存在时只是使用默认值
+        assert_eq!(Config::read(&path), Config::default());
+    }
+
+    #[test]
+    fn scheduled_profile_is_not_saved() {
+        let mut config = Config {
+            active_profile: "会议".into(),
+            profile_before_schedule: Some("编程".into()),
+            ..Config::default()
+        };
+        let saved: Config = toml::from_str(&config.to_toml().unwrap()).unwrap();
+        assert_eq!(saved.active_profile, "编程");
+        assert_eq!(saved.profile_before_schedule, None);
+
+        config.profile_before_schedule = None;
+        let saved: Config = toml::from_str(&config.to_toml().unwrap()).unwrap();
+        assert_eq!(saved.active_profile, "会议");
+    }
+}
@@ -0,0 +1,115 @@
+//! 时长的文本格式：支持 "90s"、"20m"、"1h30m" 这类写法
+//!
+//! 设置面板、配置文件与命令行共用这里的解析和格式化。
+
+use std::time::Duration;
+
+/// 工作时长允许的范围
+pub const MIN_WORK_DURATION: Duration = Duration::from_secs(5);
+pub const MAX_WORK_DURATION: Duration = Duration::from_secs(3 * 60 * 60);
+
+/// 解析时长文本；不带单位的纯数字按分钟处理，与旧版设置保持一致
+///
+/// 数值大到秒数溢出 `u64` 时视为无效。
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim().to_ascii_lowercase();
+    if text.is_empty() {
+        return None;
+    }
+    if let Ok(minutes) = text.parse::<u64>() {
+        return minutes.checked_mul(60).map(Duration::from_secs);
+    }
+
+    let mut total = 0u64;
+    let mut number = String::new();
+    for c in text.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'h' | 'm' | 's' => {
+                let value: u64 = number.parse().ok()?;
+                number.clear();
+                let secs = match c {
+                    'h' => value.checked_mul(3600)?,
+                    'm' => value.checked_mul(60)?,
+                    _ => value,
+                };
+                total = total.checked_add(secs)?;
+            }
+            c if c.is_whitespace() => {}
+            _ => return None,
+        }
+    }
+
+    // 结尾不允许有缺少单位的数字，例如 "1h30"
+    number.is_empty().then_some(Duration::from_secs(total))
+}
+
+/// 格式化为最简写法，例如 `5400s` → "1h30m"，`90s` → "1m30s"
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+
+    let mut text = String::new();
+    if h > 0 {
+        text.push_str(&format!("{}h", h));
+    }
+    if m > 0 {
+        text.push_str(&format!("{}m", m));
+    }
+    if s > 0 || text.is_empty() {
+        text.push_str(&format!("{}s", s));
+    }
+    text
+}
+
+/// 设置面板 +/- 按钮的步进：时长越短步进越细
+pub fn step_work_duration(duration: Duration, up: bool) -> Duration {
+    let secs = duration.as_secs();
+    // 向下时按 secs - 1 取步长，保证 60s 减一步是 55s 而不是 45s
+    let step = match if up { secs } else { secs.saturating_sub(1) } {
+        0..60 => 5,
+        60..300 => 15,
+        300..3600 => 60,
+        _ => 300,
+    };
+
+    let stepped = if up {
+        (secs / step + 1) * step
+    } else {
+        secs.saturating_sub(1) / step * step
+    };
+    Duration::from_secs(stepped).clamp(MIN_WORK_DURATION, MAX_WORK_DURATION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_units_and_bare_minutes() {
+        assert_eq!(parse_duration("90s"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("1h 30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("20"), Some(Duration::from_secs(1200)));
+        assert_eq!(parse_duration("1h30"), None);
+        assert_eq!(parse_duration(""), None);
+    }
+
+    #[test]
+    fn rejects_overflow() {
+        assert_eq!(parse_duration(&(u64::MAX / 60 + 1).to_string()), None);
+        assert_eq!(parse_duration(&format!("{}h", u64::MAX / 3600 + 1)), None);
+        assert_eq!(parse_duration(&format!("{}m", u64::MAX / 60 + 1)), None);
+        assert_eq!(parse_duration(&format!("{}s1s", u64::MAX)), None);
+        assert_eq!(
+            parse_duration(&format!("{}s", u64::MAX)),
+            Some(Duration::from_secs(u64::MAX))
+        );
+    }
+
+    #[test]
+    fn formats_shortest_form() {
+        assert_eq!(format_duration(Duration::from_secs(5400)), "1h30m");
+        assert_eq!(format_duration(Duration::from_secs(90)), "1m30s");
+        assert_eq!(format_duration(Duration::ZERO), "0s");
+    }
+}
@@ -0,0 +1,229 @@
+//! 无窗口模式（`--headless`）：计时照常运行，但不创建任何系统窗口
+//!
+//! 不创建主窗口和遮罩，计时逻辑与图形界面共用一份，只是不更新界面。
+//! 事件循环只处理 Slint 定时器与跨线程投递的任务；控制命令从标准输入逐行读取，
+//! HTTP 接口的命令也经这里转发。
+
+use crate::duration_format::parse_duration;
+use slint::platform::{EventLoopProxy, Platform, WindowAdapter};
+use slint::{EventLoopError, PlatformError};
+use std::cell::RefCell;
+use std::io::BufRead;
+use std::rc::Rc;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+type Task = Box<dyn FnOnce() + Send>;
+
+/// 其他线程投递给事件循环的任务与退出请求
+#[derive(Default)]
+struct Queue {
+    tasks: Mutex<(Vec<Task>, bool)>,
+    wake: Condvar,
+}
+
+impl Queue {
+    fn push(&self, task: Option<Task>) {
+        let mut pending = self.tasks.lock().unwrap();
+        match task {
+            Some(task) => pending.0.push(task),
+            None => pending.1 = true,
+        }
+        self.wake.notify_one();
+    }
+}
+
+struct HeadlessPlatform {
+    queue: Arc<Queue>,
+}
+
+struct Proxy(Arc<Queue>);
+
+impl EventLoopProxy for Proxy {
+    fn quit_event_loop(&self) -> Result<(), EventLoopError> {
+        self.0.push(None);
+        Ok(())
+    }
+
+    fn invoke_from_event_loop(&self, event: Task) -> Result<(), EventLoopError> {
+        self.0.push(Some(event));
+        Ok(())
+    }
+}
+
+impl Platform for HeadlessPlatform {
+    fn create_window_adapter(&self) -> Result<Rc<dyn WindowAdapter>, PlatformError> {
+        Err(PlatformError::Other(
+            "No windows can be created in headless mode".into(),
+        ))
+    }
+
+    fn run_event_loop(&self) -> Result<(), PlatformError> {
+        loop {
+            slint::platform::update_timers_and_animations();
+
+            let tasks = {
+                let mut pending = self.queue.tasks.lock().unwrap();
+                if pending.0.is_empty() && !pending.1 {
+                    let timeout = slint::platform::duration_until_next_timer_update();
+                    pending = match timeout {
+                        Some(timeout) => self.queue.wake.wait_timeout(pending, timeout).unwrap().0,
+                        None => self.queue.wake.wait(pending).unwrap(),
+                    };
+                }
+                if std::mem::take(&mut pending.1) {
+                    return Ok(());
+                }
+                std::mem::take(&mut pending.0)
+            };
+            for task in tasks {
+                task();
+            }
+        }
+    }
+
+    fn new_event_loop_proxy(&self) -> Option<Box<dyn EventLoopProxy>> {
+        Some(Box::new(Proxy(self.queue.clone())))
+    }
+}
+
+/// 换成无窗口的平台实现，需在创建任何组件之前调用
+pub fn install_platform() -> Result<(), PlatformError> {
+    slint::platform::set_platform(Box::new(HeadlessPlatform {
+        queue: Arc::default(),
+    }))
+    .map_err(|e| PlatformError::Other(e.to_string()))
+}
+
+/// 控制命令：来自无窗口模式的标准输入或 HTTP 接口
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    /// `pause [DURATION]`：暂停，给出时长时到期自动继续
+    Pause(Option<Duration>),
+    Resume,
+    Skip,
+    RestNow,
+    Status,
+    /// `profile NAME|N`：切换方案，见 `profile_index`
+    Profile(String),
+    Quit,
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Option<Self> {
+        // 方案名可能含空格，取命令后的整行
+        if let Some((word, target)) = line.trim().split_once(char::is_whitespace) {
+            if word == "profile" {
+                return Some(Command::Profile(target.trim().to_string()));
+            }
+        }
+        let mut words = line.split_whitespace();
+        let command = match (words.next()?, words.next()) {
+            ("pause", None) => Command::Pause(None),
+            ("pause", Some(duration)) => Command::Pause(Some(parse_duration(duration)?)),
+            ("resume", None) => Command::Resume,
+            ("skip", None) => Command::Skip,
+            ("rest", None) => Command::RestNow,
+            ("status", None) => Command::Status,
+            ("quit", None) => Command::Quit,
+            _ => return None,
+        };
+        words.next().is_none().then_some(command)
+    }
+}
+
+/// `target` 指的方案下标：先按名字匹配，再按从 1 开始的序号
+pub fn profile_index<'a>(names: impl IntoIterator<Item = &'a str>, target: &str) -> Option<usize> {
+    let names: Vec<&str> = names.into_iter().collect();
+    names.iter().position(|name| *name == target).or_else(|| {
+        let number: usize = target.parse().ok()?;
+        (1..=names.len()).contains(&number).then(|| number - 1)
+    })
+}
+
+type Dispatcher = Box<dyn Fn(Command)>;
+
+thread_local! {
+    static DISPATCHER: RefCell<Option<Dispatcher>> = const { RefCell::new(None) };
+}
+
+/// 把命令转发进事件循环，由 `dispatch` 在 UI 线程上逐个处理；标准输入与 HTTP 接口共用
+///
+/// 需要在 UI 线程调用。
+pub fn install_command_handler(dispatch: impl Fn(Command) + 'static) {
+    DISPATCHER.with(|dispatcher| *dispatcher.borrow_mut() = Some(Box::new(dispatch)));
+}
+
+/// 把命令交给 UI 线程处理，可在任意线程调用
+pub fn forward(command: Command) {
+    let _ = slint::invoke_from_event_loop(move || {
+        DISPATCHER.with(|dispatcher| {
+            if let Some(dispatch) = dispatcher.borrow().as_ref() {
+                dispatch(command);
+            }
+        });
+    });
+}
+
+/// 在后台线程逐行读取标准输入中的命令
+///
+/// 标准输入关闭后不再读取，程序继续运行（例如作为服务启动时）。
+pub fn read_commands() {
+    std::thread::spawn(|| {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            match Command::parse(&line) {
+                Some(command) => forward(command),
+                None => eprintln!("Unknown command: {}", line.trim()),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(Command::parse(" pause "), Some(Command::Pause(None)));
+        assert_eq!(
+            Command::parse("pause 1h"),
+            Some(Command::Pause(Some(Duration::from_secs(3600))))
+        );
+        assert_eq!(Command::parse("rest"), Some(Command::RestNow));
+        assert_eq!(Command::parse("pause soon"), None);
+        assert_eq!(Command::parse("skip now"), None);
+        assert_eq!(Command::parse("dance"), None);
+    }
+
+    #[test]
+    fn parses_profile_with_spaces() {
+        assert_eq!(
+            Command::parse("profile  深度 工作 "),
+            Some(Command::Profile("深度 工作".into()))
+        );
+        assert_eq!(
+            Command::parse("profile 2"),
+            Some(Command::Profile("2".into()))
+        );
+        assert_eq!(Command::parse("profile"), None);
+    }
+
+    #[test]
+    fn finds_profile_by_name_then_number() {
+        let names = ["编程", "会议", "3"];
+        assert_eq!(profile_index(names, "会议"), Some(1));
+        assert_eq!(profile_index(names, "1"), Some(0));
+        // 名字优先于序号
+        assert_eq!(profile_index(names, "3"), Some(2));
+        assert_eq!(profile_index(names, "0"), None);
+        assert_eq!(profile_index(names, "4"), None);
+        assert_eq!(profile_index(names, "游戏"), None);
+    }
+}
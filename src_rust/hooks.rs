@@ -0,0 +1,172 @@
+//! 事件钩子：计时器状态变化时执行配置中的命令，例如调暗灯光、设置聊天软件状态、静音麦克风
+//!
+//! 命令交给 shell 执行（Linux `sh -c`，Windows `cmd /C`），事件数据既放在 `ARE_YOU_BLIND_*`
+//! 环境变量中，也以一行 JSON 写入标准输入。每条命令由单独的线程等待，超时后结束进程；
+//! 失败只记录日志，不影响计时。命令的标准错误输出保留，标准输出丢弃（无窗口模式用它输出状态）。
+
+use crate::config::HookSettings;
+use serde::Serialize;
+use std::io::Write;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// 等待命令结束时检查的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 触发钩子的事件
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HookEvent {
+    WorkStarted,
+    /// 工作即将结束，提前量见 `warning_seconds`
+    Warning,
+    RestStarted,
+    RestEnded,
+    RestSkipped,
+    Paused,
+    Resumed,
+}
+
+impl HookEvent {
+    /// 配置中的键，也是 `ARE_YOU_BLIND_EVENT` 的值
+    pub fn name(self) -> &'static str {
+        match self {
+            HookEvent::WorkStarted => "work_started",
+            HookEvent::Warning => "warning",
+            HookEvent::RestStarted => "rest_started",
+            HookEvent::RestEnded => "rest_ended",
+            HookEvent::RestSkipped => "rest_skipped",
+            HookEvent::Paused => "paused",
+            HookEvent::Resumed => "resumed",
+        }
+    }
+
+    fn command(self, settings: &HookSettings) -> &str {
+        match self {
+            HookEvent::WorkStarted => &settings.work_started,
+            HookEvent::Warning => &settings.warning,
+            HookEvent::RestStarted => &settings.rest_started,
+            HookEvent::RestEnded => &settings.rest_ended,
+            HookEvent::RestSkipped => &settings.rest_skipped,
+            HookEvent::Paused => &settings.paused,
+            HookEvent::Resumed => &settings.resumed,
+        }
+    }
+}
+
+/// 事件发生时的计时器状态
+#[derive(Clone, Debug, Serialize)]
+pub struct HookPayload {
+    pub event: &'static str,
+    /// 本地时间，RFC 3339
+    pub time: String,
+    pub profile: String,
+    /// 休息相关的事件为 "eye" / "water" / "walk"，其余为 null
+    pub rest_type: Option<&'static str>,
+    pub remaining_seconds: u64,
+    pub work_seconds: u64,
+    pub rest_seconds: u64,
+    pub paused: bool,
+}
+
+impl HookPayload {
+    fn env(&self) -> [(&'static str, String); 8] {
+        [
+            ("ARE_YOU_BLIND_EVENT", self.event.to_string()),
+            ("ARE_YOU_BLIND_TIME", self.time.clone()),
+            ("ARE_YOU_BLIND_PROFILE", self.profile.clone()),
+            (
+                "ARE_YOU_BLIND_REST_TYPE",
+                self.rest_type.unwrap_or_default().to_string(),
+            ),
+            (
+                "ARE_YOU_BLIND_REMAINING_SECONDS",
+                self.remaining_seconds.to_string(),
+            ),
+            ("ARE_YOU_BLIND_WORK_SECONDS", self.work_seconds.to_string()),
+            ("ARE_YOU_BLIND_REST_SECONDS", self.rest_seconds.to_string()),
+            ("ARE_YOU_BLIND_PAUSED", self.paused.to_string()),
+        ]
+    }
+}
+
+/// 执行 `event` 对应的命令；没有配置时什么也不做
+pub fn run(settings: &HookSettings, event: HookEvent, payload: HookPayload) {
+    let command = event.command(settings).trim();
+    if command.is_empty() {
+        return;
+    }
+    let child = shell(command)
+        .envs(payload.env())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::inherit())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("Failed to run {} hook: {}", event.name(), e);
+            return;
+        }
+    };
+    let json = serde_json::to_string(&payload).unwrap_or_default();
+    let timeout = Duration::from_secs(settings.timeout_seconds);
+    std::thread::spawn(move || {
+        if let Some(mut stdin) = child.stdin.take() {
+            // 命令不读标准输入时写入会失败，忽略即可
+            let _ = writeln!(stdin, "{}", json);
+        }
+        wait(&mut child, event, timeout);
+    });
+}
+
+#[cfg(not(target_os = "windows"))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(target_os = "windows")]
+fn shell(command: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+
+    /// 不为命令弹出控制台窗口
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let mut shell = Command::new("cmd");
+    shell
+        .arg("/C")
+        .raw_arg(command)
+        .creation_flags(CREATE_NO_WINDOW);
+    shell
+}
+
+/// 等命令结束并记录失败；超时的命令被结束
+fn wait(child: &mut Child, event: HookEvent, timeout: Duration) {
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                if !status.success() {
+                    eprintln!("{} hook failed: {}", event.name(), status);
+                }
+                return;
+            }
+            Ok(None) if started.elapsed() >= timeout => {
+                eprintln!(
+                    "{} hook timed out after {}s, killing it",
+                    event.name(),
+                    timeout.as_secs()
+                );
+                let _ = child.kill();
+                let _ = child.wait();
+                return;
+            }
+            Ok(None) => std::thread::sleep(POLL_INTERVAL),
+            Err(e) => {
+                eprintln!("Failed to wait for {} hook: {}", event.name(), e);
+                return;
+            }
+        }
+    }
+}
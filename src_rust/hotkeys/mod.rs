@@ -0,0 +1,166 @@
+//! 全局快捷键：主窗口隐藏或在别的程序中时，也能暂停、立即休息、跳过下一次休息
+//!
+//! X11 会话通过 `XGrabKey` 在根窗口上抢占按键。Wayland 不允许程序直接抢键，改为通过
+//! xdg-desktop-portal 的 GlobalShortcuts 接口注册：首次注册时桌面环境会请用户确认，
+//! 最终生效的按键以桌面环境的设置为准。Windows 使用 `RegisterHotKey`。
+
+use crate::config::HotkeySettings;
+use crate::keymap::KeyChord;
+use std::any::Any;
+use std::cell::RefCell;
+
+#[cfg(target_os = "linux")]
+mod portal;
+#[cfg(windows)]
+mod windows;
+#[cfg(target_os = "linux")]
+mod x11;
+
+/// 全局快捷键触发的操作
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HotkeyAction {
+    TogglePause,
+    RestNow,
+    SkipNextRest,
+    ShowWindow,
+}
+
+impl HotkeyAction {
+    /// 在门户中注册时使用的 ID
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    fn id(self) -> &'static str {
+        match self {
+            HotkeyAction::TogglePause => "toggle-pause",
+            HotkeyAction::RestNow => "rest-now",
+            HotkeyAction::SkipNextRest => "skip-next-rest",
+            HotkeyAction::ShowWindow => "show-window",
+        }
+    }
+
+    /// 桌面环境的快捷键设置中显示的说明
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    fn description(self) -> &'static str {
+        match self {
+            HotkeyAction::TogglePause => "暂停 / 继续",
+            HotkeyAction::RestNow => "立即休息",
+            HotkeyAction::SkipNextRest => "跳过下一次休息",
+            HotkeyAction::ShowWindow => "显示主窗口",
+        }
+    }
+}
+
+type Dispatcher = Box<dyn Fn(HotkeyAction)>;
+
+thread_local! {
+    static DISPATCHER: RefCell<Option<Dispatcher>> = const { RefCell::new(None) };
+}
+
+/// 设置在 UI 线程上处理快捷键的函数；各平台的监听线程通过 `invoke_from_event_loop` 转交过来
+pub fn install_event_handler(dispatch: impl Fn(HotkeyAction) + 'static) {
+    DISPATCHER.with(|dispatcher| *dispatcher.borrow_mut() = Some(Box::new(dispatch)));
+}
+
+#[cfg_attr(not(any(target_os = "linux", windows)), allow(dead_code))]
+fn forward(action: HotkeyAction) {
+    let _ = slint::invoke_from_event_loop(move || {
+        DISPATCHER.with(|dispatcher| {
+            if let Some(dispatch) = dispatcher.borrow().as_ref() {
+                dispatch(action);
+            }
+        });
+    });
+}
+
+/// 已注册的一组全局快捷键，丢弃时注销
+#[derive(Default)]
+pub struct Hotkeys {
+    // 只为持有平台的注册状态
+    _registration: Option<Box<dyn Any>>,
+}
+
+impl Hotkeys {
+    /// 按配置注册；无法识别的写法会被忽略
+    pub fn register(settings: &HotkeySettings) -> Self {
+        let bindings: Vec<(KeyChord, HotkeyAction)> = [
+            (&settings.pause, HotkeyAction::TogglePause),
+            (&settings.rest_now, HotkeyAction::RestNow),
+            (&settings.skip_next, HotkeyAction::SkipNextRest),
+            (&settings.show_window, HotkeyAction::ShowWindow),
+        ]
+        .into_iter()
+        .filter_map(|(text, action)| Some((KeyChord::parse(text)?, action)))
+        .collect();
+        if bindings.is_empty() {
+            return Self::default();
+        }
+
+        #[cfg(target_os = "linux")]
+        let registration: Option<Box<dyn Any>> = if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            Some(Box::new(portal::register(bindings)))
+        } else {
+            x11::register(&bindings).map(|grabs| Box::new(grabs) as Box<dyn Any>)
+        };
+        #[cfg(windows)]
+        let registration: Option<Box<dyn Any>> =
+            windows::register(bindings).map(|hotkeys| Box::new(hotkeys) as Box<dyn Any>);
+        #[cfg(not(any(target_os = "linux", windows)))]
+        let registration: Option<Box<dyn Any>> = None;
+
+        Self {
+            _registration: registration,
+        }
+    }
+}
+
+/// X11 keysym 及其名称（门户的按键写法使用名称）
+#[cfg(target_os = "linux")]
+fn keysym(chord: &KeyChord) -> Option<(u32, String)> {
+    const SPECIAL: [(char, u32, &str); 9] = [
+        (' ', 0x0020, "space"),
+        ('\u{1b}', 0xff1b, "Escape"),
+        ('\n', 0xff0d, "Return"),
+        ('\t', 0xff09, "Tab"),
+        ('\u{8}', 0xff08, "BackSpace"),
+        ('\u{f700}', 0xff52, "Up"),
+        ('\u{f701}', 0xff54, "Down"),
+        ('\u{f702}', 0xff51, "Left"),
+        ('\u{f703}', 0xff53, "Right"),
+    ];
+    const PUNCTUATION: [(char, &str); 12] = [
+        (',', "comma"),
+        ('.', "period"),
+        ('/', "slash"),
+        (';', "semicolon"),
+        ('\'', "apostrophe"),
+        ('[', "bracketleft"),
+        (']', "bracketright"),
+        ('\\', "backslash"),
+        ('-', "minus"),
+        ('=', "equal"),
+        ('`', "grave"),
+        ('+', "plus"),
+    ];
+
+    if let Some(n) = chord.function_key() {
+        // XK_F1 = 0xffbe，F2–F12 依次递增
+        return Some((0xffbe + n - 1, format!("F{}", n)));
+    }
+    let c = chord.key_char()?;
+    if let Some((_, sym, name)) = SPECIAL.iter().find(|(key, _, _)| *key == c) {
+        return Some((*sym, name.to_string()));
+    }
+    if c.is_control() || ('\u{f700}'..='\u{f8ff}').contains(&c) {
+        return None;
+    }
+    let name = PUNCTUATION
+        .iter()
+        .find(|(key, _)| *key == c)
+        .map_or_else(|| c.to_string(), |(_, name)| name.to_string());
+    // Latin-1 字符的 keysym 就是码位，其余 Unicode 字符加上 0x0100_0000
+    let sym = if (c as u32) < 0x100 {
+        c as u32
+    } else {
+        0x0100_0000 | c as u32
+    };
+    Some((sym, name))
+}
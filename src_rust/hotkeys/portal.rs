@@ -0,0 +1,227 @@
+//! Wayland：通过 xdg-desktop-portal 的 GlobalShortcuts 接口注册快捷键
+//!
+//! 门户的请求要等用户在桌面环境的对话框中确认，所以整个注册和监听都在后台线程中进行。
+
+use super::{forward, keysym, HotkeyAction};
+use crate::keymap::KeyChord;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use zbus::blocking::{Connection, MessageIterator, Proxy};
+use zbus::message::Type as MessageType;
+use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
+use zbus::MatchRule;
+
+const DESTINATION: &str = "org.freedesktop.portal.Desktop";
+const PATH: &str = "/org/freedesktop/portal/desktop";
+const INTERFACE: &str = "org.freedesktop.portal.GlobalShortcuts";
+const SESSION_INTERFACE: &str = "org.freedesktop.portal.Session";
+
+type Results = HashMap<String, OwnedValue>;
+/// 后台线程的连接与会话，丢弃时用来关闭
+type Session = Arc<Mutex<(Option<Connection>, Option<OwnedObjectPath>)>>;
+
+/// 门户中的一个会话，丢弃时关闭，快捷键随之失效
+pub struct PortalHotkeys {
+    session: Session,
+    closed: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+pub fn register(bindings: Vec<(KeyChord, HotkeyAction)>) -> PortalHotkeys {
+    let session = Session::default();
+    let closed = Arc::new(AtomicBool::new(false));
+
+    let thread_session = session.clone();
+    let thread_closed = closed.clone();
+    let thread = std::thread::spawn(move || {
+        if let Err(e) = run(&bindings, &thread_session, &thread_closed) {
+            // 注销时连接被关闭，正在进行的请求会失败，不用记录
+            if !thread_closed.load(Ordering::SeqCst) {
+                eprintln!(
+                    "Failed to register global hotkeys through the portal: {}",
+                    e
+                );
+            }
+        }
+    });
+
+    PortalHotkeys {
+        session,
+        closed,
+        thread: Some(thread),
+    }
+}
+
+fn run(
+    bindings: &[(KeyChord, HotkeyAction)],
+    session_slot: &Session,
+    closed: &AtomicBool,
+) -> zbus::Result<()> {
+    let conn = Connection::session()?;
+    {
+        let mut slot = session_slot.lock().unwrap();
+        // 连接期间已被注销
+        if closed.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        slot.0 = Some(conn.clone());
+    }
+    let portal = Proxy::new(&conn, DESTINATION, PATH, INTERFACE)?;
+
+    let session_token = next_token();
+    let created = request(&conn, |token| {
+        let options = HashMap::from([
+            ("handle_token", Value::from(token)),
+            ("session_handle_token", Value::from(session_token.as_str())),
+        ]);
+        portal.call("CreateSession", &(options,))
+    })?;
+    // 规范中是字符串，部分实现返回对象路径
+    let session = match created.get("session_handle").map(|value| &**value) {
+        Some(Value::Str(path)) => OwnedObjectPath::try_from(path.as_str())?,
+        Some(Value::ObjectPath(path)) => OwnedObjectPath::from(path.clone()),
+        _ => return Err(zbus::Error::Failure("portal returned no session".into())),
+    };
+    {
+        let mut slot = session_slot.lock().unwrap();
+        // 会话创建期间已被注销
+        if closed.load(Ordering::SeqCst) {
+            close_session(&conn, &session);
+            return Ok(());
+        }
+        slot.1 = Some(session.clone());
+    }
+
+    // 先订阅再绑定，避免漏掉确认后立即触发的事件
+    let rule = MatchRule::builder()
+        .msg_type(MessageType::Signal)
+        .sender(DESTINATION)?
+        .build();
+    let signals = MessageIterator::for_match_rule(rule, &conn, None)?;
+
+    let shortcuts: Vec<(&str, HashMap<&str, Value>)> = bindings
+        .iter()
+        .map(|(chord, action)| {
+            let mut properties =
+                HashMap::from([("description", Value::from(action.description()))]);
+            if let Some(trigger) = preferred_trigger(chord) {
+                properties.insert("preferred_trigger", Value::from(trigger));
+            }
+            (action.id(), properties)
+        })
+        .collect();
+    request(&conn, |token| {
+        let options = HashMap::from([("handle_token", Value::from(token))]);
+        portal.call("BindShortcuts", &(&session, shortcuts, "", options))
+    })?;
+
+    for message in signals {
+        if closed.load(Ordering::SeqCst) {
+            break;
+        }
+        let message = message?;
+        let header = message.header();
+        let interface = header.interface().map(|name| name.as_str());
+        let member = header.member().map(|name| name.as_str());
+        match (interface, member) {
+            (Some(INTERFACE), Some("Activated")) => {
+                let (activated, id, _timestamp, _options): (OwnedObjectPath, String, u64, Results) =
+                    message.body().deserialize()?;
+                if activated != session {
+                    continue;
+                }
+                if let Some((_, action)) = bindings.iter().find(|(_, action)| action.id() == id) {
+                    forward(*action);
+                }
+            }
+            (Some(SESSION_INTERFACE), Some("Closed"))
+                if header.path().is_some_and(|path| *path == *session) =>
+            {
+                break;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// 发起一个门户请求并等待它的 Response 信号
+///
+/// 请求对象的路径由调用方的连接名和 `handle_token` 决定，先订阅再调用才不会错过回复。
+fn request(
+    conn: &Connection,
+    call: impl FnOnce(&str) -> zbus::Result<OwnedObjectPath>,
+) -> zbus::Result<Results> {
+    let token = next_token();
+    let sender = conn
+        .unique_name()
+        .map(|name| name.trim_start_matches(':').replace('.', "_"))
+        .unwrap_or_default();
+    let path = format!("{}/request/{}/{}", PATH, sender, token);
+    let request = Proxy::new(conn, DESTINATION, path, "org.freedesktop.portal.Request")?;
+    let mut responses = request.receive_signal("Response")?;
+
+    call(&token)?;
+    let response = responses
+        .next()
+        .ok_or_else(|| zbus::Error::Failure("portal request was dropped".into()))?;
+    let (code, results): (u32, Results) = response.body().deserialize()?;
+    match code {
+        0 => Ok(results),
+        1 => Err(zbus::Error::Failure("cancelled by the user".into())),
+        _ => Err(zbus::Error::Failure("request failed".into())),
+    }
+}
+
+/// 请求与会话的 token，在进程内唯一
+fn next_token() -> String {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    format!(
+        "are_you_blind_{}_{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// XDG 快捷键规范的写法，例如 "CTRL+ALT+p"
+fn preferred_trigger(chord: &KeyChord) -> Option<String> {
+    let (_, name) = keysym(chord)?;
+    let mut trigger = String::new();
+    for (on, modifier) in [
+        (chord.ctrl, "CTRL+"),
+        (chord.alt, "ALT+"),
+        (chord.shift, "SHIFT+"),
+        (chord.meta, "LOGO+"),
+    ] {
+        if on {
+            trigger.push_str(modifier);
+        }
+    }
+    trigger.push_str(&name);
+    Some(trigger)
+}
+
+fn close_session(conn: &Connection, session: &OwnedObjectPath) {
+    if let Ok(proxy) = Proxy::new(conn, DESTINATION, session, SESSION_INTERFACE) {
+        let _ = proxy.call_method("Close", &());
+    }
+}
+
+impl Drop for PortalHotkeys {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::SeqCst);
+        let (conn, session) = std::mem::take(&mut *self.session.lock().unwrap());
+        if let Some(conn) = conn {
+            if let Some(session) = session {
+                close_session(&conn, &session);
+            }
+            // 关闭连接后监听线程等待中的信号迭代立即结束，不必等下一条门户信号
+            let _ = conn.close();
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
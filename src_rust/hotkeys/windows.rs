@@ -0,0 +1,155 @@
+//! Windows：`RegisterHotKey` 的消息发到注册线程的消息队列，由专门的线程注册并接收
+
+#![allow(clippy::upper_case_acronyms)]
+
+use super::{forward, HotkeyAction};
+use crate::keymap::KeyChord;
+use std::thread::JoinHandle;
+
+type BOOL = i32;
+type HWND = isize;
+
+#[repr(C)]
+struct POINT {
+    x: i32,
+    y: i32,
+}
+
+#[repr(C)]
+struct MSG {
+    hwnd: HWND,
+    message: u32,
+    w_param: usize,
+    l_param: isize,
+    time: u32,
+    pt: POINT,
+}
+
+const MOD_ALT: u32 = 0x0001;
+const MOD_CONTROL: u32 = 0x0002;
+const MOD_SHIFT: u32 = 0x0004;
+const MOD_WIN: u32 = 0x0008;
+const MOD_NOREPEAT: u32 = 0x4000;
+const WM_QUIT: u32 = 0x0012;
+const WM_HOTKEY: u32 = 0x0312;
+
+#[link(name = "user32")]
+extern "system" {
+    fn RegisterHotKey(hwnd: HWND, id: i32, modifiers: u32, vk: u32) -> BOOL;
+    fn UnregisterHotKey(hwnd: HWND, id: i32) -> BOOL;
+    fn GetMessageW(msg: *mut MSG, hwnd: HWND, filter_min: u32, filter_max: u32) -> BOOL;
+    fn PostThreadMessageW(thread_id: u32, msg: u32, w_param: usize, l_param: isize) -> BOOL;
+    fn VkKeyScanW(c: u16) -> i16;
+}
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn GetCurrentThreadId() -> u32;
+}
+
+/// 注册快捷键的线程，丢弃时让它注销并等它退出
+pub struct WindowsHotkeys {
+    thread_id: u32,
+    thread: Option<JoinHandle<()>>,
+}
+
+pub fn register(bindings: Vec<(KeyChord, HotkeyAction)>) -> Option<WindowsHotkeys> {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let thread = std::thread::spawn(move || {
+        let mut registered = Vec::new();
+        for (index, (chord, action)) in bindings.iter().enumerate() {
+            let Some((vk, shifted)) = virtual_key(chord) else {
+                eprintln!("Unsupported global hotkey: {}", chord);
+                continue;
+            };
+            let id = index as i32 + 1;
+            let modifiers = modifier_flags(chord, shifted) | MOD_NOREPEAT;
+            if unsafe { RegisterHotKey(0, id, modifiers, vk) } != 0 {
+                registered.push((id, *action));
+            } else {
+                eprintln!(
+                    "Global hotkey {} is already taken by another program",
+                    chord
+                );
+            }
+        }
+        // 上面的调用已经创建了消息队列，此后 PostThreadMessageW 才能送达
+        let _ = sender.send(unsafe { GetCurrentThreadId() });
+
+        let mut msg: MSG = unsafe { std::mem::zeroed() };
+        while unsafe { GetMessageW(&mut msg, 0, 0, 0) } > 0 {
+            if msg.message != WM_HOTKEY {
+                continue;
+            }
+            if let Some((_, action)) = registered
+                .iter()
+                .find(|(id, _)| *id as usize == msg.w_param)
+            {
+                forward(*action);
+            }
+        }
+        for (id, _) in registered {
+            unsafe {
+                UnregisterHotKey(0, id);
+            }
+        }
+    });
+
+    receiver.recv().ok().map(|thread_id| WindowsHotkeys {
+        thread_id,
+        thread: Some(thread),
+    })
+}
+
+/// 虚拟键码，以及该字符是否需要按 Shift 才能输入
+fn virtual_key(chord: &KeyChord) -> Option<(u32, bool)> {
+    if let Some(n) = chord.function_key() {
+        // VK_F1 = 0x70
+        return Some((0x70 + n - 1, false));
+    }
+    let vk = match chord.key_char()? {
+        c @ ('a'..='z' | '0'..='9') => c.to_ascii_uppercase() as u32,
+        ' ' => 0x20,
+        '\u{1b}' => 0x1b,
+        '\n' => 0x0d,
+        '\t' => 0x09,
+        '\u{8}' => 0x08,
+        '\u{f700}' => 0x26,
+        '\u{f701}' => 0x28,
+        '\u{f702}' => 0x25,
+        '\u{f703}' => 0x27,
+        c => {
+            // 符号键的位置取决于键盘布局；高字节的最低位表示需要 Shift
+            let scan = unsafe { VkKeyScanW(u16::try_from(c as u32).ok()?) };
+            if scan == -1 {
+                return None;
+            }
+            return Some(((scan & 0xff) as u32, scan & 0x100 != 0));
+        }
+    };
+    Some((vk, false))
+}
+
+fn modifier_flags(chord: &KeyChord, shifted: bool) -> u32 {
+    [
+        (chord.alt, MOD_ALT),
+        (chord.ctrl, MOD_CONTROL),
+        (chord.shift || shifted, MOD_SHIFT),
+        (chord.meta, MOD_WIN),
+    ]
+    .into_iter()
+    .filter(|(on, _)| *on)
+    .fold(0, |flags, (_, flag)| flags | flag)
+}
+
+impl Drop for WindowsHotkeys {
+    fn drop(&mut self) {
+        unsafe {
+            PostThreadMessageW(self.thread_id, WM_QUIT, 0, 0);
+        }
+        // 修改快捷键时紧接着会注册新的一组，相同的组合键要等这里注销完才能再注册
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
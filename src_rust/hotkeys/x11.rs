@@ -0,0 +1,158 @@
+//! X11：在根窗口上抢占按键，由后台线程等待按键事件
+
+use super::{forward, keysym, HotkeyAction};
+use crate::keymap::KeyChord;
+use std::sync::Arc;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{
+    AtomEnum, ClientMessageEvent, ConnectionExt as _, CreateWindowAux, EventMask, GrabMode,
+    Keycode, ModMask, Window, WindowClass,
+};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+
+/// 匹配时忽略的锁定键：CapsLock 与 NumLock（通常是 Mod2）
+const LOCKS: u16 = 0x0002 | 0x0010;
+
+/// 锁定键打开时按键的状态不同，每个组合都要分别抢占
+const LOCK_STATES: [u16; 4] = [0, 0x0002, 0x0010, LOCKS];
+
+/// 已抢占的按键，丢弃时释放并结束监听线程
+pub struct X11Hotkeys {
+    conn: Arc<RustConnection>,
+    root: Window,
+    /// 用来唤醒监听线程让它退出的隐藏窗口
+    wakeup: Window,
+    grabs: Vec<(Keycode, u16)>,
+}
+
+pub fn register(bindings: &[(KeyChord, HotkeyAction)]) -> Option<X11Hotkeys> {
+    let (conn, screen) = x11rb::connect(None).ok()?;
+    let root = conn.setup().roots.get(screen)?.root;
+    let (min_keycode, max_keycode) = (conn.setup().min_keycode, conn.setup().max_keycode);
+    let mapping = conn
+        .get_keyboard_mapping(min_keycode, max_keycode - min_keycode + 1)
+        .ok()?
+        .reply()
+        .ok()?;
+    let per_keycode = (mapping.keysyms_per_keycode as usize).max(1);
+
+    let mut actions = Vec::new();
+    for (chord, action) in bindings {
+        // 找到产生该 keysym 的键；在第二列（Shift 层）找到时需要同时按 Shift
+        let found = keysym(chord).and_then(|(sym, _)| {
+            mapping
+                .keysyms
+                .chunks(per_keycode)
+                .enumerate()
+                .find_map(|(index, syms)| {
+                    let column = syms.iter().position(|s| *s == sym)?;
+                    Some((min_keycode + index as u8, column % 2 == 1))
+                })
+        });
+        let Some((keycode, shifted)) = found else {
+            eprintln!("Unsupported global hotkey: {}", chord);
+            continue;
+        };
+        let modifiers = modifier_mask(chord, shifted);
+
+        let mut grabbed = true;
+        for locks in LOCK_STATES {
+            grabbed &= conn
+                .grab_key(
+                    false,
+                    root,
+                    ModMask::from(modifiers | locks),
+                    keycode,
+                    GrabMode::ASYNC,
+                    GrabMode::ASYNC,
+                )
+                .ok()
+                .is_some_and(|cookie| cookie.check().is_ok());
+        }
+        if !grabbed {
+            eprintln!(
+                "Global hotkey {} is already taken by another program",
+                chord
+            );
+        }
+        actions.push((keycode, modifiers, *action));
+    }
+
+    let wakeup = conn.generate_id().ok()?;
+    conn.create_window(
+        0,
+        wakeup,
+        root,
+        0,
+        0,
+        1,
+        1,
+        0,
+        WindowClass::INPUT_ONLY,
+        0,
+        &CreateWindowAux::new(),
+    )
+    .ok()?;
+    conn.flush().ok()?;
+
+    let grabs = actions
+        .iter()
+        .map(|(keycode, modifiers, _)| (*keycode, *modifiers))
+        .collect();
+    let conn = Arc::new(conn);
+    let thread_conn = conn.clone();
+    std::thread::spawn(move || {
+        while let Ok(event) = thread_conn.wait_for_event() {
+            match event {
+                Event::KeyPress(event) => {
+                    let state = u16::from(event.state) & !LOCKS;
+                    if let Some((_, _, action)) = actions.iter().find(|(keycode, modifiers, _)| {
+                        *keycode == event.detail && *modifiers == state
+                    }) {
+                        forward(*action);
+                    }
+                }
+                Event::ClientMessage(event) if event.window == wakeup => break,
+                _ => {}
+            }
+        }
+    });
+
+    Some(X11Hotkeys {
+        conn,
+        root,
+        wakeup,
+        grabs,
+    })
+}
+
+fn modifier_mask(chord: &KeyChord, shifted: bool) -> u16 {
+    [
+        (chord.shift || shifted, ModMask::SHIFT),
+        (chord.ctrl, ModMask::CONTROL),
+        (chord.alt, ModMask::M1),
+        (chord.meta, ModMask::M4),
+    ]
+    .into_iter()
+    .filter(|(on, _)| *on)
+    .fold(0, |mask, (_, modifier)| mask | u16::from(modifier))
+}
+
+impl Drop for X11Hotkeys {
+    fn drop(&mut self) {
+        for (keycode, modifiers) in &self.grabs {
+            for locks in LOCK_STATES {
+                let _ = self
+                    .conn
+                    .ungrab_key(*keycode, self.root, ModMask::from(modifiers | locks));
+            }
+        }
+        let event = ClientMessageEvent::new(32, self.wakeup, AtomEnum::NONE, [0u32; 5]);
+        let _ = self
+            .conn
+            .send_event(false, self.wakeup, EventMask::NO_EVENT, event);
+        let _ = self.conn.destroy_window(self.wakeup);
+        let _ = self.conn.flush();
+    }
+}
@@ -0,0 +1,289 @@
+//! 本机 HTTP 接口：给状态栏（waybar、polybar）和家庭仪表盘读取计时状态、发送控制命令
+//!
+//! 只监听 127.0.0.1，默认关闭。每个连接由单独的线程处理，响应后关闭连接；同时处理的连接
+//! 超过上限时直接返回 503。`/events` 保持连接，以 Server-Sent Events 推送状态变化与钩子事件。
+//! 状态由 UI 线程在每次 tick 时写入，控制命令经 `headless::forward` 交给 UI 线程执行。
+//!
+//! | 请求 | 说明 |
+//! |------|------|
+//! | `GET /status` | 当前状态（JSON） |
+//! | `GET /events` | 事件流：`status` 以及各钩子事件 |
+//! | `POST /pause`、`/resume`、`/skip`、`/rest-now` | 与托盘菜单相同的操作，返回 204 |
+//! | `POST /profile` | 切换到请求体中的方案（名字或从 1 开始的序号），没有该方案时返回 404 |
+
+use crate::headless::{self, profile_index, Command};
+use serde::Serialize;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 读取请求头的超时，避免空连接一直占着线程
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// 请求头与请求体各自的长度上限
+const MAX_HEADER_BYTES: usize = 8 * 1024;
+/// 同时处理请求的线程数上限；`/events` 的订阅者交给推送之后不再占用
+const MAX_CONNECTIONS: usize = 16;
+
+/// UI 线程最近一次写入的计时状态
+#[derive(Clone, Debug, PartialEq)]
+pub struct Status {
+    /// "work" / "rest" / "awaiting_return"
+    pub mode: &'static str,
+    pub paused: bool,
+    /// 休息中为 "eye" / "water" / "walk"
+    pub rest_type: Option<&'static str>,
+    pub profile: String,
+    /// 所有方案的名字，按设置中的顺序
+    pub profiles: Vec<String>,
+    /// 写入时的剩余时间
+    pub remaining: Duration,
+    /// 剩余时间是否在减少（工作或休息中且没有暂停）
+    pub counting: bool,
+    /// 今天完成的休息次数
+    pub breaks_today: u32,
+}
+
+#[derive(Serialize)]
+struct StatusJson<'a> {
+    mode: &'a str,
+    paused: bool,
+    rest_type: Option<&'a str>,
+    profile: &'a str,
+    profiles: &'a [String],
+    remaining_seconds: u64,
+    breaks_today: u32,
+}
+
+struct Shared {
+    status: Mutex<(Status, Instant)>,
+    /// 订阅了 `/events` 的连接
+    clients: Mutex<Vec<TcpStream>>,
+    /// 正在处理请求的连接数
+    active: AtomicUsize,
+}
+
+/// 占用一个连接名额，处理结束（包括 panic）时归还
+struct Slot(Arc<Shared>);
+
+impl Slot {
+    fn acquire(shared: &Arc<Shared>) -> Option<Self> {
+        shared
+            .active
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |active| {
+                (active < MAX_CONNECTIONS).then_some(active + 1)
+            })
+            .ok()?;
+        Some(Self(shared.clone()))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.active.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl Shared {
+    /// 按写入后经过的时间推算当前的剩余时间
+    fn status_json(&self) -> String {
+        let (status, updated) = &*self.status.lock().unwrap();
+        let remaining = if status.counting {
+            status.remaining.saturating_sub(updated.elapsed())
+        } else {
+            status.remaining
+        };
+        let json = StatusJson {
+            mode: status.mode,
+            paused: status.paused,
+            rest_type: status.rest_type,
+            profile: &status.profile,
+            profiles: &status.profiles,
+            remaining_seconds: remaining.as_secs(),
+            breaks_today: status.breaks_today,
+        };
+        serde_json::to_string(&json).unwrap_or_default()
+    }
+
+    /// 推送给所有订阅者，写入失败的连接视为已断开
+    fn broadcast(&self, event: &str, data: &str) {
+        let message = format!("event: {}\ndata: {}\n\n", event, data);
+        self.clients
+            .lock()
+            .unwrap()
+            .retain_mut(|client| client.write_all(message.as_bytes()).is_ok());
+    }
+}
+
+pub struct HttpServer {
+    shared: Arc<Shared>,
+}
+
+impl HttpServer {
+    /// 在 127.0.0.1:`port` 上开始监听
+    pub fn start(port: u16, status: Status) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        let shared = Arc::new(Shared {
+            status: Mutex::new((status, Instant::now())),
+            clients: Mutex::new(Vec::new()),
+            active: AtomicUsize::new(0),
+        });
+        let accept_shared = shared.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let Some(slot) = Slot::acquire(&accept_shared) else {
+                    let _ = respond(stream, "503 Service Unavailable", None);
+                    continue;
+                };
+                std::thread::spawn(move || {
+                    if let Err(e) = handle(stream, &slot.0) {
+                        eprintln!("Failed to handle HTTP request: {}", e);
+                    }
+                });
+            }
+        });
+        Ok(Self { shared })
+    }
+
+    /// 写入最新状态；除剩余时间外有变化时推送 `status` 事件
+    pub fn update(&self, status: Status) {
+        let changed = {
+            let mut current = self.shared.status.lock().unwrap();
+            let changed = Status {
+                remaining: current.0.remaining,
+                counting: current.0.counting,
+                ..status.clone()
+            } != current.0;
+            *current = (status, Instant::now());
+            changed
+        };
+        if changed {
+            self.shared.broadcast("status", &self.shared.status_json());
+        }
+    }
+
+    /// 推送一个钩子事件
+    pub fn publish(&self, event: &str, data: &impl Serialize) {
+        if let Ok(data) = serde_json::to_string(data) {
+            self.shared.broadcast(event, &data);
+        }
+    }
+}
+
+fn handle(stream: TcpStream, shared: &Shared) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    // 请求行与请求头一起限长：没有换行的超长行也只读到上限为止
+    let mut head = reader.by_ref().take(MAX_HEADER_BYTES as u64);
+
+    let mut request_line = String::new();
+    head.read_line(&mut request_line)?;
+    if head.limit() == 0 {
+        return respond(stream, "431 Request Header Fields Too Large", None);
+    }
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return respond(stream, "400 Bad Request", None);
+    };
+
+    let (mut host_ok, mut has_origin, mut content_length) = (false, false, 0);
+    loop {
+        let mut line = String::new();
+        let read = head.read_line(&mut line)?;
+        if head.limit() == 0 {
+            return respond(stream, "431 Request Header Fields Too Large", None);
+        }
+        if read == 0 || line.trim().is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        if name.eq_ignore_ascii_case("host") {
+            host_ok = is_local_host(value.trim());
+        } else if name.eq_ignore_ascii_case("origin") {
+            has_origin = true;
+        } else if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.trim().parse().unwrap_or(0);
+        }
+    }
+    // 只有 `/profile` 用到请求体，但都要读完：关闭时还有未读的数据会重置连接，客户端可能收不到响应
+    if content_length > MAX_HEADER_BYTES {
+        return respond(stream, "413 Content Too Large", None);
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    // 网页里的脚本也能访问 localhost：带 Origin 的跨站请求与 DNS 重绑定（Host 不是本机）一律拒绝
+    if !host_ok || has_origin {
+        return respond(stream, "403 Forbidden", None);
+    }
+
+    let path = path.split('?').next().unwrap_or_default();
+    let command = match (method, path) {
+        ("GET", "/status") => return respond(stream, "200 OK", Some(&shared.status_json())),
+        ("GET", "/events") => return subscribe(stream, shared),
+        ("POST", "/pause") => Command::Pause(None),
+        ("POST", "/resume") => Command::Resume,
+        ("POST", "/skip") => Command::Skip,
+        ("POST", "/rest-now") => Command::RestNow,
+        ("POST", "/profile") => {
+            let target = String::from_utf8_lossy(&body).trim().to_string();
+            let known = {
+                let status = &shared.status.lock().unwrap().0;
+                profile_index(status.profiles.iter().map(String::as_str), &target).is_some()
+            };
+            if !known {
+                return respond(stream, "404 Not Found", None);
+            }
+            Command::Profile(target)
+        }
+        (_, "/status" | "/events" | "/pause" | "/resume" | "/skip" | "/rest-now" | "/profile") => {
+            return respond(stream, "405 Method Not Allowed", None)
+        }
+        _ => return respond(stream, "404 Not Found", None),
+    };
+    headless::forward(command);
+    respond(stream, "204 No Content", None)
+}
+
+/// `localhost`、`127.0.0.1` 或 `[::1]`，可以带端口
+fn is_local_host(host: &str) -> bool {
+    let name = match host.rsplit_once(':') {
+        Some((name, port)) if !name.ends_with(':') && port.parse::<u16>().is_ok() => name,
+        _ => host,
+    };
+    matches!(name, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+fn respond(mut stream: TcpStream, status: &str, json: Option<&str>) -> io::Result<()> {
+    let body = json.unwrap_or_default();
+    let content_type = if json.is_some() {
+        "Content-Type: application/json\r\n"
+    } else {
+        ""
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+}
+
+/// 发送响应头与当前状态，之后由 `broadcast` 继续写入
+///
+/// 推送在 UI 线程进行，连接改为非阻塞：读得慢的订阅者写不进去时直接断开，不会卡住界面。
+fn subscribe(mut stream: TcpStream, shared: &Shared) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\r\n\
+         event: status\ndata: {}\n\n",
+        shared.status_json()
+    )?;
+    stream.set_nonblocking(true)?;
+    shared.clients.lock().unwrap().push(stream);
+    Ok(())
+}
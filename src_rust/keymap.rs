@@ -0,0 +1,178 @@
+//! 键盘快捷键：解析配置中的按键写法，并把按键映射到操作
+//!
+//! 按键写作 `"Space"`、`"S"`、`","`、`"Escape"`、`"Ctrl+Alt+P"`，不区分大小写。
+
+use crate::config::KeyBindings;
+use std::fmt;
+
+/// 快捷键对应的操作
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyAction {
+    TogglePause,
+    Skip,
+    OpenSettings,
+    HideToTray,
+    /// 遮罩上：推迟这次休息
+    Postpone,
+}
+
+/// 一个按键组合；`key` 为 Slint `KeyEvent.text` 的小写形式
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyChord {
+    pub key: String,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub meta: bool,
+}
+
+/// 有名字的按键与 Slint 中对应的字符
+const NAMED_KEYS: [(&str, char); 11] = [
+    ("space", ' '),
+    ("escape", '\u{1b}'),
+    ("esc", '\u{1b}'),
+    ("enter", '\n'),
+    ("return", '\n'),
+    ("tab", '\t'),
+    ("backspace", '\u{8}'),
+    ("up", '\u{f700}'),
+    ("down", '\u{f701}'),
+    ("left", '\u{f702}'),
+    ("right", '\u{f703}'),
+];
+
+/// Slint 中 F1 对应的字符，F2–F12 依次递增
+const F1: u32 = 0xf704;
+
+/// Slint 中 Shift、Control、Alt、AltGr、CapsLock 及左右 Meta 等修饰键本身的字符
+const MODIFIER_KEYS: std::ops::RangeInclusive<char> = '\u{10}'..='\u{18}';
+
+impl KeyChord {
+    /// 解析配置中的写法；无法识别时返回 None
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut chord = Self::from_event("", false, false, false, false);
+        // 最后一段是按键本身，允许单独的 "+" 作为按键
+        let (modifiers, key) = match text.trim().rsplit_once('+') {
+            Some((modifiers, "")) => (modifiers.strip_suffix('+').unwrap_or(modifiers), "+"),
+            Some((modifiers, key)) => (modifiers, key),
+            None => ("", text.trim()),
+        };
+        for modifier in modifiers.split('+').filter(|m| !m.is_empty()) {
+            match modifier.trim().to_lowercase().as_str() {
+                "ctrl" | "control" => chord.ctrl = true,
+                "alt" => chord.alt = true,
+                "shift" => chord.shift = true,
+                "meta" | "super" | "win" | "cmd" => chord.meta = true,
+                _ => return None,
+            }
+        }
+
+        let key = key.trim().to_lowercase();
+        chord.key = if let Some((_, c)) = NAMED_KEYS.iter().find(|(name, _)| *name == key) {
+            c.to_string()
+        } else if let Some(n) = key
+            .strip_prefix('f')
+            .and_then(|n| n.parse::<u32>().ok())
+            .filter(|n| (1..=12).contains(n))
+        {
+            char::from_u32(F1 + n - 1)?.to_string()
+        } else if key.chars().count() == 1 {
+            key
+        } else {
+            return None;
+        };
+        Some(chord)
+    }
+
+    /// 由 Slint 的按键事件构造
+    ///
+    /// 符号键的 Shift 已经体现在字符里（`?` 即 Shift+/），只对字母和功能键记录 Shift。
+    pub fn from_event(text: &str, ctrl: bool, alt: bool, shift: bool, meta: bool) -> Self {
+        let key = text.to_lowercase();
+        let shift = shift
+            && key
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_alphabetic() || c.is_control() || c >= '\u{f700}');
+        Self {
+            key,
+            ctrl,
+            alt,
+            shift,
+            meta,
+        }
+    }
+
+    /// 按键本身的字符（小写）
+    pub fn key_char(&self) -> Option<char> {
+        let mut chars = self.key.chars();
+        chars.next().filter(|_| chars.next().is_none())
+    }
+
+    /// F1–F12 返回 1–12
+    pub fn function_key(&self) -> Option<u32> {
+        let n = (self.key_char()? as u32).checked_sub(F1)? + 1;
+        (n <= 12).then_some(n)
+    }
+
+    /// 只按下了修饰键本身，例如录入快捷键时先按下的 Ctrl
+    pub fn is_modifier(&self) -> bool {
+        self.key_char().is_some_and(|c| MODIFIER_KEYS.contains(&c))
+    }
+}
+
+/// 与 `parse` 互逆的写法，例如 "Ctrl+Alt+P"
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (on, name) in [
+            (self.ctrl, "Ctrl"),
+            (self.alt, "Alt"),
+            (self.shift, "Shift"),
+            (self.meta, "Meta"),
+        ] {
+            if on {
+                write!(f, "{}+", name)?;
+            }
+        }
+        let Some(c) = self.key_char() else {
+            return f.write_str(&self.key);
+        };
+        if let Some((name, _)) = NAMED_KEYS.iter().find(|(_, key)| *key == c) {
+            let mut chars = name.chars();
+            let first = chars.next().unwrap_or_default().to_ascii_uppercase();
+            write!(f, "{}{}", first, chars.as_str())
+        } else if let Some(n) = self.function_key() {
+            write!(f, "F{}", n)
+        } else {
+            write!(f, "{}", c.to_uppercase())
+        }
+    }
+}
+
+/// 配置中的快捷键表
+pub struct Keymap {
+    bindings: Vec<(KeyChord, KeyAction)>,
+}
+
+impl Keymap {
+    pub fn new(keys: &KeyBindings) -> Self {
+        let bindings = [
+            (&keys.pause, KeyAction::TogglePause),
+            (&keys.skip, KeyAction::Skip),
+            (&keys.settings, KeyAction::OpenSettings),
+            (&keys.hide, KeyAction::HideToTray),
+            (&keys.postpone, KeyAction::Postpone),
+        ]
+        .into_iter()
+        .filter_map(|(text, action)| Some((KeyChord::parse(text)?, action)))
+        .collect();
+        Self { bindings }
+    }
+
+    pub fn action(&self, pressed: &KeyChord) -> Option<KeyAction> {
+        self.bindings
+            .iter()
+            .find(|(chord, _)| chord == pressed)
+            .map(|(_, action)| *action)
+    }
+}
@@ -0,0 +1,9 @@
+//! 瞎了么的共享库：平台相关代码、登录时启动与不依赖窗口的逻辑
+//!
+//! `are_you_blind` 与 `dump_monitors` 两个可执行文件都链接这里，
+//! 这样显示器枚举只有一份实现，遮罩摆放与主窗口位置的计算也可以配合 `MockDisplays` 单独测试。
+
+pub mod autostart;
+pub mod overlay_layout;
+pub mod platform;
+pub mod window_placement;
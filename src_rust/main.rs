@@ -1,97 +1,117 @@
 #![windows_subsystem = "windows"]
-#![allow(clippy::upper_case_acronyms)] // Windows API types use uppercase names
 
+mod audio;
+mod backdrop;
+mod cli;
+mod config;
+mod duration_format;
+mod headless;
+mod hooks;
+mod hotkeys;
+mod http;
+mod keymap;
+mod notify;
+mod theme;
+mod ticker;
+mod tray;
+mod tray_render;
+
+use are_you_blind::autostart;
+use are_you_blind::overlay_layout::{detail_monitor, filter_outputs, plan_overlays};
+use are_you_blind::platform::{self, CursorTracker, MonitorRect, MonitorWatch, WorkArea};
+use are_you_blind::window_placement;
+use audio::{Audio, Sound};
+use backdrop::Slideshow;
+use chrono::{DateTime, Local, NaiveDate, Timelike};
+use cli::Options;
+use config::{
+    parse_hex_color, Config, HotkeySettings, MessagePack, OverlaySettings, OverlayStyle,
+    Strictness, ThemeMode, WindowPlacement,
+};
+use duration_format::{
+    format_duration, parse_duration, step_work_duration, MAX_WORK_DURATION, MIN_WORK_DURATION,
+};
+use headless::Command;
+use hooks::{HookEvent, HookPayload};
+use hotkeys::{HotkeyAction, Hotkeys};
+use http::HttpServer;
+use image::RgbaImage;
+use keymap::{KeyAction, KeyChord, Keymap};
 use rand::seq::SliceRandom;
-use slint::{SharedString, Timer, TimerMode};
+use slint::{Color, ModelRc, SharedString, Timer, VecModel};
 use std::cell::RefCell;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 use std::time::{Duration, Instant};
-use tray_icon::{
-    menu::{Menu, MenuEvent, MenuItem},
-    TrayIconBuilder, TrayIconEvent,
-};
+use ticker::Ticker;
+use tray::{Tray, TrayAction, TrayEvent, TrayStatus};
+use tray_icon::{MouseButton, MouseButtonState, TrayIconEvent};
+use tray_render::IconPhase;
 
 slint::include_modules!();
 
-#[cfg(target_os = "windows")]
-fn enable_windows_per_monitor_dpi_awareness() {
-    use std::ffi::c_void;
-
-    type HMODULE = *mut c_void;
-    type FARPROC = *mut c_void;
-    type BOOL = i32;
-    type HRESULT = i32;
-
-    const PROCESS_PER_MONITOR_DPI_AWARE: i32 = 2;
-
-    fn wide_null_terminated(s: &str) -> Vec<u16> {
-        s.encode_utf16().chain(std::iter::once(0)).collect()
-    }
-
-    #[link(name = "kernel32")]
-    extern "system" {
-        fn LoadLibraryW(lp_lib_file_name: *const u16) -> HMODULE;
-        fn GetProcAddress(h_module: HMODULE, lp_proc_name: *const i8) -> FARPROC;
-    }
-
-    unsafe {
-        let user32 = LoadLibraryW(wide_null_terminated("user32.dll").as_ptr());
-        if !user32.is_null() {
-            let set_context = GetProcAddress(user32, c"SetProcessDpiAwarenessContext".as_ptr());
-            if !set_context.is_null() {
-                type SetProcessDpiAwarenessContextFn =
-                    unsafe extern "system" fn(*mut c_void) -> BOOL;
-                let set_context: SetProcessDpiAwarenessContextFn = std::mem::transmute(set_context);
-                if set_context((-4isize) as *mut c_void) != 0 {
-                    return;
-                }
-            }
-        }
-
-        let shcore = LoadLibraryW(wide_null_terminated("shcore.dll").as_ptr());
-        if !shcore.is_null() {
-            let set_awareness = GetProcAddress(shcore, c"SetProcessDpiAwareness".as_ptr());
-            if !set_awareness.is_null() {
-                type SetProcessDpiAwarenessFn = unsafe extern "system" fn(i32) -> HRESULT;
-                let set_awareness: SetProcessDpiAwarenessFn = std::mem::transmute(set_awareness);
-                if set_awareness(PROCESS_PER_MONITOR_DPI_AWARE) == 0 {
-                    return;
-                }
-            }
-        }
-
-        if !user32.is_null() {
-            let set_dpi_aware = GetProcAddress(user32, c"SetProcessDPIAware".as_ptr());
-            if !set_dpi_aware.is_null() {
-                type SetProcessDPIAwareFn = unsafe extern "system" fn() -> BOOL;
-                let set_dpi_aware: SetProcessDPIAwareFn = std::mem::transmute(set_dpi_aware);
-                let _ = set_dpi_aware();
-            }
-        }
-    }
-}
+/// 检查方案自动切换规则的间隔
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+/// 主计时器两次唤醒的最长间隔
+const MAX_TICK_INTERVAL: Duration = Duration::from_secs(60);
+/// 唤醒时间略晚于边界，保证醒来时显示已经变化
+const TICK_SLACK: Duration = Duration::from_millis(5);
+/// 在遮罩上推迟休息时，多久之后再次提醒
+const POSTPONE_DURATION: Duration = Duration::from_secs(5 * 60);
+/// 迷你小窗的逻辑尺寸，与 appwindow.slint 中 `compact` 时的窗口尺寸一致
+const COMPACT_SIZE: (f32, f32) = (168.0, 40.0);
+/// 迷你小窗默认放在右上角时与屏幕边缘的距离（逻辑像素）
+const COMPACT_MARGIN: f32 = 16.0;
+/// 迷你模式下鼠标停在小窗上这么久才接收点击，匆匆划过时点击穿透到下面的窗口
+const COMPACT_HOVER_DWELL: Duration = Duration::from_millis(400);
+/// 迷你模式轮询鼠标位置的间隔
+const COMPACT_HOVER_POLL: Duration = Duration::from_millis(100);
 
 struct AppState {
+    config: Config,
     is_paused: bool,
+    pause_until: Option<DateTime<Local>>, // 定时暂停的结束时间
     work_duration: Duration,
     rest_duration: Duration,
+    work_override: Option<Duration>, // 命令行指定的时长，本次运行中切换方案后仍然生效
+    rest_override: Option<Duration>,
+    water_enabled: bool,
     water_interval: u32, // 每几轮护眼提醒后触发喝水提醒
-    walk_interval: u32,  // 每几轮护眼提醒后触发走动提醒
+    walk_enabled: bool,
+    walk_interval: u32, // 每几轮护眼提醒后触发走动提醒
+    strictness: Strictness,
+    message_pack: MessagePack,
+    await_return: bool,  // 休息结束后等待用户回来再开始计时
     eye_rest_count: u32, // 当前护眼提醒计数
     current_mode: Mode,
     current_rest_type: RestType, // 当前休息类型
     start_time: Instant,
     last_tick: Instant,
     overlay_windows: Vec<OverlayWindowEntry>,
+    overlay_content: Option<OverlayContent>,
+    monitor_watch: MonitorWatch,
+    slideshow: Slideshow, // 遮罩背景图片的轮换
+    audio: Audio,
+    keymap: Keymap,
+    hotkeys: Hotkeys,
+    skip_next_rest: bool,           // 下一次工作结束时不休息，直接开始新一轮
+    warned: bool,                   // 本轮工作已触发过 warning 钩子
+    breaks_today: (NaiveDate, u32), // 当天完成的休息次数
+    http: Option<HttpServer>,
     main_window_visible: bool,
+    tray_enabled: bool, // 没有托盘时隐藏窗口改为最小化
+    headless: bool,     // 无窗口模式：不显示遮罩，提醒改为桌面通知
+    compact_hover: CompactHover,
     drag_anchor_window_pos: Option<slint::LogicalPosition>,
     drag_anchor_pointer_screen_pos: Option<slint::LogicalPosition>,
+    schedule_rule: Option<usize>, // 当前生效的自动切换规则
+    last_schedule_check: Option<Instant>,
 }
 
 #[derive(PartialEq, Clone, Copy)]
 enum Mode {
     Work,
     Rest,
+    AwaitingReturn, // 休息已结束，等待用户回到电脑前
 }
 
 #[derive(PartialEq, Clone, Copy)]
@@ -101,23 +121,113 @@ enum RestType {
     Walk,    // 走动提醒
 }
 
-impl Default for AppState {
-    fn default() -> Self {
-        Self {
+impl AppState {
+    fn new(config: Config, headless: bool) -> Self {
+        let keymap = Keymap::new(&config.keys);
+        // 无窗口模式没有窗口可显示，也不抢占系统的全局快捷键
+        let hotkeys = if headless {
+            Hotkeys::default()
+        } else {
+            Hotkeys::register(&config.hotkeys)
+        };
+        let mut state = Self {
+            config,
             is_paused: false,
+            pause_until: None,
             work_duration: Duration::from_secs(20 * 60),
             rest_duration: Duration::from_secs(20),
+            work_override: None,
+            rest_override: None,
+            water_enabled: true,
             water_interval: 2,
+            walk_enabled: true,
             walk_interval: 3,
+            strictness: Strictness::Normal,
+            message_pack: MessagePack::Mixed,
+            await_return: false,
             eye_rest_count: 0,
             current_mode: Mode::Work,
             current_rest_type: RestType::EyeRest,
             start_time: Instant::now(),
             last_tick: Instant::now(),
             overlay_windows: Vec::new(),
+            overlay_content: None,
+            monitor_watch: MonitorWatch::from_env(),
+            slideshow: Slideshow::default(),
+            audio: Audio::new(audio::system_backend()),
+            keymap,
+            hotkeys,
+            skip_next_rest: false,
+            warned: false,
+            breaks_today: (Local::now().date_naive(), 0),
+            http: None,
             main_window_visible: true,
+            tray_enabled: true,
+            headless,
+            compact_hover: CompactHover::default(),
             drag_anchor_window_pos: None,
             drag_anchor_pointer_screen_pos: None,
+            schedule_rule: None,
+            last_schedule_check: None,
+        };
+        state.load_active_profile();
+        state
+    }
+
+    /// 把当前方案的设置加载到运行状态，命令行指定的时长优先
+    fn load_active_profile(&mut self) {
+        let profile = self.config.active().clone();
+        self.work_duration = self.work_override.unwrap_or(profile.work);
+        self.rest_duration = self
+            .rest_override
+            .unwrap_or(Duration::from_secs(profile.rest_seconds as u64));
+        self.water_enabled = profile.water_enabled;
+        self.water_interval = profile.water_interval;
+        self.walk_enabled = profile.walk_enabled;
+        self.walk_interval = profile.walk_interval;
+        self.strictness = profile.strictness;
+        self.message_pack = profile.message_pack;
+        self.await_return = profile.await_return;
+    }
+
+    fn current_limit(&self) -> Duration {
+        match self.current_mode {
+            Mode::Work => self.work_duration,
+            Mode::Rest => self.rest_duration,
+            Mode::AwaitingReturn => Duration::ZERO,
+        }
+    }
+
+    fn remaining(&self) -> Duration {
+        self.current_limit()
+            .saturating_sub(self.start_time.elapsed())
+    }
+
+    fn can_skip(&self) -> bool {
+        !(self.current_mode == Mode::Rest && self.strictness == Strictness::Strict)
+    }
+
+    /// 按自动切换规则返回需要切换到的方案序号
+    fn scheduled_profile(&mut self) -> Option<usize> {
+        let rule = self.config.current_schedule_rule();
+        if rule == self.schedule_rule {
+            return None;
+        }
+        self.schedule_rule = rule;
+
+        match rule {
+            Some(rule) => {
+                if self.config.profile_before_schedule.is_none() {
+                    self.config.profile_before_schedule = Some(self.config.active_profile.clone());
+                }
+                self.config
+                    .profile_index(&self.config.schedule[rule].profile)
+            }
+            None => self
+                .config
+                .profile_before_schedule
+                .take()
+                .and_then(|name| self.config.profile_index(&name)),
         }
     }
 }
@@ -129,17 +239,39 @@ fn format_duration_mm_ss(duration: Duration) -> SharedString {
     SharedString::from(format!("{:02}:{:02}", mins, secs))
 }
 
+/// 按文案风格组合候选提示；`Plain` 返回空列表，由调用方使用固定文案
+fn message_pool<'a>(
+    pack: MessagePack,
+    basic: &[(&'a str, &'a str)],
+    science: &[(&'a str, &'a str)],
+    humor: &[(&'a str, &'a str)],
+    cheer: &[(&'a str, &'a str)],
+) -> Vec<(&'a str, &'a str)> {
+    let groups: &[&[(&str, &str)]] = match pack {
+        MessagePack::Mixed => &[basic, science, humor, cheer],
+        MessagePack::Science => &[basic, science],
+        MessagePack::Humor => &[basic, humor, cheer],
+        MessagePack::Plain => &[],
+    };
+    groups
+        .iter()
+        .flat_map(|group| group.iter().copied())
+        .collect()
+}
+
 /// 获取随机的护眼休息提示信息
-fn get_eye_rest_message(rest_seconds: u64) -> (&'static str, String) {
-    let messages: Vec<(&str, &str)> = vec![
-        // 20-20-20 法则相关
+fn get_eye_rest_message(rest_seconds: u64, pack: MessagePack) -> (&'static str, String) {
+    // 20-20-20 法则相关
+    let basic = [
         ("👀 护眼时间", "20-20-20 法则：每 20 分钟看 20 英尺外 {} 秒"),
         (
             "🌳 远眺时刻",
             "把目光投向窗外最远的地方，让睫状肌放松 {} 秒",
         ),
         ("🏔️ 望远休息", "想象你在山顶眺望远方，眼睛自然放松 {} 秒"),
-        // 科普类 - 眼睛健康
+    ];
+    // 科普类 - 眼睛健康
+    let science = [
         (
             "💡 护眼小知识",
             "人平均每分钟眨眼 15-20 次，专注屏幕时可能降到 3-4 次！休息 {} 秒",
@@ -168,7 +300,26 @@ fn get_eye_rest_message(rest_seconds: u64) -> (&'static str, String) {
             "🔍 视力保护",
             "眼睛干涩？可能是泪膜蒸发太快。多眨眼，休息 {} 秒",
         ),
-        // 幽默诙谐类
+        // 健康危害警示（轻松版）
+        (
+            "⚠️ 温馨提示",
+            "长时间盯屏幕可能导致头痛、肩颈酸痛。起来活动 {} 秒吧",
+        ),
+        (
+            "🏥 眼科医生说",
+            "干眼症患者越来越年轻化了，休息 {} 秒预防一下",
+        ),
+        (
+            "💊 不吃药的处方",
+            "治疗眼疲劳最好的药：休息 {} 秒 + 远眺绿色植物",
+        ),
+        (
+            "🩺 健康小贴士",
+            "眼疲劳会引起头痛，头痛会影响心情，心情差会摸鱼。休息 {} 秒吧",
+        ),
+    ];
+    // 幽默诙谐类
+    let humor = [
         (
             "🦉 猫头鹰说",
             "即使我能 270° 转头，也需要休息。你的脖子只能转 180°，更该歇歇了！{} 秒",
@@ -203,48 +354,36 @@ fn get_eye_rest_message(rest_seconds: u64) -> (&'static str, String) {
         ),
         ("🎬 导演喊卡", "导演说：\"卡！\" 眼睛杀青休息 {} 秒"),
         ("🎭 眼睛罢工", "您的眼睛申请了 {} 秒带薪休假，已批准"),
-        // 健康危害警示（轻松版）
-        (
-            "⚠️ 温馨提示",
-            "长时间盯屏幕可能导致头痛、肩颈酸痛。起来活动 {} 秒吧",
-        ),
-        (
-            "🏥 眼科医生说",
-            "干眼症患者越来越年轻化了，休息 {} 秒预防一下",
-        ),
-        (
-            "💊 不吃药的处方",
-            "治疗眼疲劳最好的药：休息 {} 秒 + 远眺绿色植物",
-        ),
-        (
-            "🩺 健康小贴士",
-            "眼疲劳会引起头痛，头痛会影响心情，心情差会摸鱼。休息 {} 秒吧",
-        ),
-        // 激励类
+    ];
+    // 激励类
+    let cheer = [
         ("💪 效率提升", "适当休息反而能提高工作效率。科学划水 {} 秒"),
         ("🧠 大脑充电", "让眼睛和大脑同步休息 {} 秒，待会儿更清醒"),
         ("⚡ 能量恢复", "短暂休息能恢复注意力，这 {} 秒是值得的投资"),
         ("🎯 专注重置", "暂停是为了更好地出发。休息 {} 秒，重新聚焦"),
     ];
 
-    let (headline, template) = messages
+    let (headline, template) = message_pool(pack, &basic, &science, &humor, &cheer)
         .choose(&mut rand::thread_rng())
-        .unwrap_or(&("👀 护眼时间", "休息 {} 秒，保护视力"));
+        .copied()
+        .unwrap_or(("👀 护眼时间", "休息 {} 秒，保护视力"));
 
-    (*headline, template.replace("{}", &rest_seconds.to_string()))
+    (headline, template.replace("{}", &rest_seconds.to_string()))
 }
 
 /// 获取随机的喝水提示信息
-fn get_water_message(rest_seconds: u64) -> (&'static str, String) {
-    let messages: Vec<(&str, &str)> = vec![
-        // 基础提醒
+fn get_water_message(rest_seconds: u64, pack: MessagePack) -> (&'static str, String) {
+    // 基础提醒
+    let basic = [
         ("💧 喝水时间", "起来喝杯水吧！保持身体水分充足（{} 秒）"),
         (
             "🚰 补水提醒",
             "该喝水啦！人体 70% 是水，别让自己\"干涸\"（{} 秒）",
         ),
         ("🥤 饮水时刻", "水是生命之源，现在就喝一杯吧（{} 秒）"),
-        // 科普类 - 喝水的重要性
+    ];
+    // 科普类 - 喝水的重要性、不喝水的危害
+    let science = [
         (
             "📊 健康数据",
             "人每天需要 2000ml 水，你今天喝够了吗？（{} 秒）",
@@ -257,7 +396,6 @@ fn get_water_message(rest_seconds: u64) -> (&'static str, String) {
         ("💡 喝水冷知识", "大脑 75% 是水，缺水会变\"笨\"哦（{} 秒）"),
         ("🌡️ 温度建议", "温水 (35-40°C) 最容易被身体吸收（{} 秒）"),
         ("⏰ 喝水时机", "起床、饭前、运动后是喝水的黄金时间（{} 秒）"),
-        // 不喝水的危害
         (
             "⚠️ 缺水警告",
             "缺水会导致头痛、疲劳、皮肤干燥。快补水！（{} 秒）",
@@ -271,7 +409,9 @@ fn get_water_message(rest_seconds: u64) -> (&'static str, String) {
             "感觉累？可能不是困，是渴！喝杯水试试（{} 秒）",
         ),
         ("🤯 大脑求救", "头昏脑涨？你的大脑在喊渴！（{} 秒）"),
-        // 幽默诙谐类
+    ];
+    // 幽默诙谐类
+    let humor = [
         (
             "🐫 骆驼都笑了",
             "骆驼：我能 7 天不喝水，你可不行！（{} 秒）",
@@ -290,27 +430,37 @@ fn get_water_message(rest_seconds: u64) -> (&'static str, String) {
             "Coffee++ 不如 Water++，少喝咖啡多喝水（{} 秒）",
         ),
         ("🦴 骨骼精奇", "关节润滑需要水，别让自己\"生锈\"（{} 秒）"),
-        // 激励类
+    ];
+    // 激励类
+    let cheer = [
         ("✨ 美容秘方", "多喝水是最便宜的护肤品（{} 秒）"),
         ("🏃 代谢加速", "喝水能促进新陈代谢，助力减脂（{} 秒）"),
         ("🎯 效率提升", "充足饮水能让你保持清醒专注（{} 秒）"),
     ];
 
-    let (headline, template) = messages
+    let (headline, template) = message_pool(pack, &basic, &science, &humor, &cheer)
         .choose(&mut rand::thread_rng())
-        .unwrap_or(&("💧 喝水时间", "起来喝杯水吧（{} 秒）"));
+        .copied()
+        .unwrap_or(("💧 喝水时间", "起来喝杯水吧（{} 秒）"));
 
-    (*headline, template.replace("{}", &rest_seconds.to_string()))
+    (headline, template.replace("{}", &rest_seconds.to_string()))
 }
 
 /// 获取随机的走动提示信息
-fn get_walk_message(rest_seconds: u64) -> (&'static str, String) {
-    let messages: Vec<(&str, &str)> = vec![
-        // 基础提醒
+fn get_walk_message(rest_seconds: u64, pack: MessagePack) -> (&'static str, String) {
+    // 基础提醒、建议动作
+    let basic = [
         ("🚶 走动时间", "站起来活动一下身体！（{} 秒）"),
         ("🏃 运动时刻", "久坐是健康杀手，起来动动吧（{} 秒）"),
         ("🧘 伸展提醒", "伸个懒腰，活动筋骨（{} 秒）"),
-        // 科普类 - 久坐危害
+        ("🤸 推荐动作", "试试原地高抬腿，激活下肢肌肉（{} 秒）"),
+        ("🧘 办公室瑜伽", "站起来做几个深蹲，唤醒臀部肌肉（{} 秒）"),
+        ("💃 扭一扭", "扭扭腰，转转头，活动全身关节（{} 秒）"),
+        ("🏃 小跑一下", "绕办公室走一圈，或原地踏步（{} 秒）"),
+        ("🙆 伸展运动", "双手举过头顶，做个全身伸展（{} 秒）"),
+    ];
+    // 科普类 - 久坐危害、身体部位提醒
+    let science = [
         (
             "📊 久坐数据",
             "久坐超过 1 小时，预期寿命减少 22 分钟！（{} 秒）",
@@ -330,7 +480,6 @@ fn get_walk_message(rest_seconds: u64) -> (&'static str, String) {
             "久坐让心血管疾病风险增加 147%！起来活动（{} 秒）",
         ),
         ("🧠 大脑供血", "站起来能增加大脑供血，思路更清晰（{} 秒）"),
-        // 身体部位提醒
         ("🦵 腿部呼救", "你的腿想念走路的感觉了！（{} 秒）"),
         (
             "🦴 脊椎请求",
@@ -342,7 +491,9 @@ fn get_walk_message(rest_seconds: u64) -> (&'static str, String) {
             "关节需要运动来分泌润滑液，别让它们\"生锈\"（{} 秒）",
         ),
         ("👣 脚趾活动", "动动脚趾，促进下肢血液循环（{} 秒）"),
-        // 幽默诙谐类
+    ];
+    // 幽默诙谐类
+    let humor = [
         ("🐢 乌龟都着急", "连乌龟都比你动得多，起来走走！（{} 秒）"),
         ("🦥 树懒震惊", "树懒：没想到有人比我还懒！（{} 秒）"),
         ("🪑 椅子抗议", "你的椅子申请轮换休息了（{} 秒）"),
@@ -358,346 +509,304 @@ fn get_walk_message(rest_seconds: u64) -> (&'static str, String) {
             "📱 步数挑战",
             "微信运动 100 步也是步数，起来贡献一下（{} 秒）",
         ),
-        // 建议动作
-        ("🤸 推荐动作", "试试原地高抬腿，激活下肢肌肉（{} 秒）"),
-        ("🧘 办公室瑜伽", "站起来做几个深蹲，唤醒臀部肌肉（{} 秒）"),
-        ("💃 扭一扭", "扭扭腰，转转头，活动全身关节（{} 秒）"),
-        ("🏃 小跑一下", "绕办公室走一圈，或原地踏步（{} 秒）"),
-        ("🙆 伸展运动", "双手举过头顶，做个全身伸展（{} 秒）"),
-        // 激励类
+    ];
+    // 激励类
+    let cheer = [
         ("⚡ 能量激活", "活动一下，血液循环加速，精力充沛（{} 秒）"),
         ("🎯 效率秘诀", "适当活动能让下午不犯困（{} 秒）"),
         ("✨ 健康投资", "每天多走 2000 步，一年下来了不起（{} 秒）"),
     ];
 
-    let (headline, template) = messages
+    let (headline, template) = message_pool(pack, &basic, &science, &humor, &cheer)
         .choose(&mut rand::thread_rng())
-        .unwrap_or(&("🚶 走动时间", "站起来活动一下身体（{} 秒）"));
+        .copied()
+        .unwrap_or(("🚶 走动时间", "站起来活动一下身体（{} 秒）"));
 
-    (*headline, template.replace("{}", &rest_seconds.to_string()))
+    (headline, template.replace("{}", &rest_seconds.to_string()))
 }
 
-#[cfg(target_os = "windows")]
 struct OverlayWindowEntry {
     window: RestOverlayWindow,
-    monitor: MonitorRect,
+    /// 覆盖的显示器；`None` 表示无法枚举显示器时的兜底窗口
+    monitor: Option<MonitorRect>,
 }
 
-#[cfg(not(target_os = "windows"))]
-struct OverlayWindowEntry {
-    window: RestOverlayWindow,
+/// 本次休息遮罩上显示的内容，热插拔新建的遮罩也要用到
+struct OverlayContent {
+    headline: SharedString,
+    message: SharedString,
+    exercise: Exercise,
+    look: OverlayLook,
+    waiting: bool,
+    can_skip: bool,
+    key_hint: SharedString,
+    handlers: OverlayHandlers,
 }
 
-#[cfg(target_os = "windows")]
-#[allow(dead_code)]
-#[derive(Clone, Copy, Debug)]
-struct MonitorRect {
-    x: i32,
-    y: i32,
-    width: u32,
-    height: u32,
-    scale_factor: f32,
-}
-
-#[cfg(target_os = "windows")]
-fn monitor_rects() -> Vec<MonitorRect> {
-    use std::ffi::c_void;
-    use std::mem::MaybeUninit;
-    use std::ptr;
-
-    type HMONITOR = *mut c_void;
-    type HDC = *mut c_void;
-    type LPARAM = isize;
-    type BOOL = i32;
-    type UINT = u32;
-    type HRESULT = i32;
-    const CCHDEVICENAME: usize = 32;
-    const CCHFORMNAME: usize = 32;
-    const ENUM_CURRENT_SETTINGS: u32 = 0xFFFF_FFFF;
-    const MDT_EFFECTIVE_DPI: i32 = 0;
-
-    #[repr(C)]
-    struct RECT {
-        left: i32,
-        top: i32,
-        right: i32,
-        bottom: i32,
-    }
-
-    #[repr(C)]
-    #[allow(non_snake_case)]
-    struct MONITORINFOEXW {
-        cbSize: u32,
-        rcMonitor: RECT,
-        rcWork: RECT,
-        dwFlags: u32,
-        szDevice: [u16; CCHDEVICENAME],
-    }
-
-    #[repr(C)]
-    #[allow(non_snake_case)]
-    struct POINTL {
-        x: i32,
-        y: i32,
-    }
-
-    #[repr(C)]
-    #[allow(non_snake_case)]
-    struct DEVMODEW {
-        dmDeviceName: [u16; CCHDEVICENAME],
-        dmSpecVersion: u16,
-        dmDriverVersion: u16,
-        dmSize: u16,
-        dmDriverExtra: u16,
-        dmFields: u32,
-        dmPosition: POINTL,
-        dmDisplayOrientation: u32,
-        dmDisplayFixedOutput: u32,
-        dmColor: i16,
-        dmDuplex: i16,
-        dmYResolution: i16,
-        dmTTOption: i16,
-        dmCollate: i16,
-        dmFormName: [u16; CCHFORMNAME],
-        dmLogPixels: u16,
-        dmBitsPerPel: u32,
-        dmPelsWidth: u32,
-        dmPelsHeight: u32,
-        dmDisplayFlags: u32,
-        dmDisplayFrequency: u32,
-        dmICMMethod: u32,
-        dmICMIntent: u32,
-        dmMediaType: u32,
-        dmDitherType: u32,
-        dmReserved1: u32,
-        dmReserved2: u32,
-        dmPanningWidth: u32,
-        dmPanningHeight: u32,
-    }
-
-    type MonitorEnumProc =
-        Option<unsafe extern "system" fn(HMONITOR, HDC, *mut RECT, LPARAM) -> BOOL>;
-
-    #[link(name = "user32")]
-    extern "system" {
-        fn EnumDisplayMonitors(
-            hdc: HDC,
-            lprcClip: *const RECT,
-            lpfnEnum: MonitorEnumProc,
-            dwData: LPARAM,
-        ) -> BOOL;
-        fn GetMonitorInfoW(hMonitor: HMONITOR, lpmi: *mut MONITORINFOEXW) -> BOOL;
-        fn EnumDisplaySettingsW(
-            lpszDeviceName: *const u16,
-            iModeNum: u32,
-            lpDevMode: *mut DEVMODEW,
-        ) -> BOOL;
-    }
-
-    #[link(name = "Shcore")]
-    extern "system" {
-        fn GetDpiForMonitor(
-            hmonitor: HMONITOR,
-            dpiType: i32,
-            dpiX: *mut UINT,
-            dpiY: *mut UINT,
-        ) -> HRESULT;
-    }
-
-    unsafe extern "system" fn enum_monitor_cb(
-        hmonitor: HMONITOR,
-        _hdc: HDC,
-        _rect: *mut RECT,
-        data: LPARAM,
-    ) -> BOOL {
-        let monitors: &mut Vec<MonitorRect> = &mut *(data as *mut Vec<MonitorRect>);
-
-        let mut mi = MaybeUninit::<MONITORINFOEXW>::zeroed();
-        (*mi.as_mut_ptr()).cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
-        if GetMonitorInfoW(hmonitor, mi.as_mut_ptr()) != 0 {
-            let mi = mi.assume_init();
-
-            let mut rect: Option<MonitorRect> = None;
-
-            let mut scale_factor = 1.0f32;
-            let mut dpi_x: UINT = 0;
-            let mut dpi_y: UINT = 0;
-            if GetDpiForMonitor(hmonitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y) == 0
-                && dpi_x > 0
-            {
-                scale_factor = dpi_x as f32 / 96.0;
-            }
+/// 遮罩窗口的回调
+#[derive(Clone)]
+struct OverlayHandlers {
+    /// 「跳过」按钮，以及等待返回时的任意操作
+    on_skip: Rc<dyn Fn()>,
+    /// 遮罩上的快捷键，返回是否已处理
+    on_shortcut: Rc<dyn Fn(KeyChord) -> bool>,
+}
 
-            let mut dm = MaybeUninit::<DEVMODEW>::zeroed();
-            (*dm.as_mut_ptr()).dmSize = std::mem::size_of::<DEVMODEW>() as u16;
-            if EnumDisplaySettingsW(mi.szDevice.as_ptr(), ENUM_CURRENT_SETTINGS, dm.as_mut_ptr())
-                != 0
-            {
-                let dm = dm.assume_init();
-                rect = Some(MonitorRect {
-                    x: dm.dmPosition.x,
-                    y: dm.dmPosition.y,
-                    width: dm.dmPelsWidth,
-                    height: dm.dmPelsHeight,
-                    scale_factor,
-                });
-            } else {
-                let r = mi.rcMonitor;
-                let width = (r.right - r.left).max(0) as u32;
-                let height = (r.bottom - r.top).max(0) as u32;
-                if width > 0 && height > 0 {
-                    rect = Some(MonitorRect {
-                        x: r.left,
-                        y: r.top,
-                        width,
-                        height,
-                        scale_factor,
-                    });
-                }
-            }
+/// 主显示器遮罩上的跟练动画，数值与 `RestOverlayWindow.exercise` 对应
+#[derive(Clone, Copy, PartialEq)]
+enum Exercise {
+    None = 0,
+    EyeCircle = 1, // 眼球跟着圆点转动
+    FarNear = 2,   // 随圆圈远近调节焦点
+    Stretch = 3,   // 起身伸展
+}
 
-            if let Some(rect) = rect {
-                if rect.width > 0 && rect.height > 0 {
-                    monitors.push(rect);
-                }
-            }
-        }
+/// 遮罩的背景与字体，每次休息开始时按配置和主窗口主题确定
+#[derive(Clone)]
+struct OverlayLook {
+    style: OverlayStyle,
+    opacity: f32,
+    /// 原始图片，创建遮罩时再按显示器尺寸缩放
+    image: Option<Rc<RgbaImage>>,
+    gradient: [Color; 2],
+    accent: Color,
+    high_contrast: bool,
+    text_scale: f32,
+    font_family: SharedString,
+    mono_font_family: SharedString,
+}
+
+impl OverlayLook {
+    fn new(settings: &OverlaySettings, slideshow: &mut Slideshow, palette: &AppPalette) -> Self {
+        let image = match (settings.style, &settings.image_dir, &settings.image) {
+            (OverlayStyle::Image, Some(dir), _) => slideshow.next(dir),
+            (OverlayStyle::Image, None, Some(path)) => backdrop::load(path),
+            _ => None,
+        };
+        // 图片读取失败时退回纯黑，而不是显示一片空白
+        let style = match settings.style {
+            OverlayStyle::Image if image.is_none() => OverlayStyle::Solid,
+            style => style,
+        };
+        let defaults = OverlaySettings::default();
+        let gradient = [0, 1].map(|i| {
+            let [r, g, b] = parse_hex_color(&settings.gradient[i])
+                .or_else(|| parse_hex_color(&defaults.gradient[i]))
+                .unwrap_or_default();
+            Color::from_rgb_u8(r, g, b)
+        });
 
-        1
+        Self {
+            style,
+            opacity: settings.opacity.clamp(0.2, 1.0),
+            image,
+            gradient,
+            accent: palette.get_accent_color(),
+            high_contrast: palette.get_high_contrast(),
+            text_scale: palette.get_text_scale(),
+            font_family: palette.get_font_family(),
+            mono_font_family: palette.get_mono_font_family(),
+        }
     }
 
-    let mut monitors = Vec::new();
-    unsafe {
-        let _ = EnumDisplayMonitors(
-            ptr::null_mut(),
-            ptr::null(),
-            Some(enum_monitor_cb),
-            (&mut monitors as *mut Vec<MonitorRect>) as LPARAM,
-        );
+    fn apply(&self, overlay: &RestOverlayWindow, monitor: Option<&MonitorRect>) {
+        overlay.set_background_style(self.style.index() as i32);
+        overlay.set_dim_opacity(self.opacity);
+        if let Some(image) = &self.image {
+            // 按显示器物理像素缩放裁剪；兜底窗口尺寸未知，交给 image-fit 处理
+            let image = match monitor {
+                Some(monitor) => backdrop::to_slint_image(&backdrop::fit_cover(
+                    image,
+                    monitor.width,
+                    monitor.height,
+                )),
+                None => backdrop::to_slint_image(image),
+            };
+            overlay.set_background_image(image);
+        }
+        overlay.set_gradient_start(self.gradient[0]);
+        overlay.set_gradient_end(self.gradient[1]);
+
+        let palette = overlay.global::<AppPalette>();
+        palette.set_accent_color(self.accent);
+        palette.set_high_contrast(self.high_contrast);
+        palette.set_text_scale(self.text_scale);
+        palette.set_font_family(self.font_family.clone());
+        palette.set_mono_font_family(self.mono_font_family.clone());
     }
-    monitors
 }
 
-#[cfg(target_os = "windows")]
 fn fit_overlay_to_monitor(entry: &OverlayWindowEntry) {
     let window = entry.window.window();
-    window.set_position(slint::PhysicalPosition::new(
-        entry.monitor.x,
-        entry.monitor.y,
-    ));
-
-    let scale_factor = window.scale_factor().max(0.1);
-    let logical_width = (entry.monitor.width as f32) / scale_factor + 1.0;
-    let logical_height = (entry.monitor.height as f32) / scale_factor + 1.0;
 
-    window.set_size(slint::LogicalSize::new(logical_width, logical_height));
-}
+    // 兜底窗口：Windows 覆盖整个虚拟桌面，其他平台交给窗口管理器全屏
+    #[cfg(target_os = "windows")]
+    let fallback = platform::virtual_screen_rect();
+    #[cfg(target_os = "windows")]
+    let monitor = entry.monitor.as_ref().unwrap_or(&fallback);
 
-#[cfg(target_os = "windows")]
-fn virtual_screen_rect() -> MonitorRect {
-    use std::ffi::c_int;
+    #[cfg(not(target_os = "windows"))]
+    let Some(monitor) = &entry.monitor
+    else {
+        window.set_fullscreen(true);
+        return;
+    };
 
-    #[link(name = "user32")]
-    extern "system" {
-        fn GetSystemMetrics(nIndex: c_int) -> c_int;
+    // Wayland 不允许客户端自己摆放窗口，只能请求在指定输出上全屏
+    #[cfg(target_os = "linux")]
+    if fullscreen_on_monitor(window, monitor) {
+        return;
     }
 
-    const SM_XVIRTUALSCREEN: c_int = 76;
-    const SM_YVIRTUALSCREEN: c_int = 77;
-    const SM_CXVIRTUALSCREEN: c_int = 78;
-    const SM_CYVIRTUALSCREEN: c_int = 79;
+    window.set_position(slint::PhysicalPosition::new(monitor.x, monitor.y));
 
-    let x = unsafe { GetSystemMetrics(SM_XVIRTUALSCREEN) };
-    let y = unsafe { GetSystemMetrics(SM_YVIRTUALSCREEN) };
-    let width = unsafe { GetSystemMetrics(SM_CXVIRTUALSCREEN) }.max(1) as u32;
-    let height = unsafe { GetSystemMetrics(SM_CYVIRTUALSCREEN) }.max(1) as u32;
+    let scale_factor = window.scale_factor().max(0.1);
+    let logical_width = (monitor.width as f32) / scale_factor + 1.0;
+    let logical_height = (monitor.height as f32) / scale_factor + 1.0;
 
-    MonitorRect {
-        x,
-        y,
-        width,
-        height,
-        scale_factor: 1.0,
-    }
+    window.set_size(slint::LogicalSize::new(logical_width, logical_height));
 }
 
-fn show_rest_overlay(state: &mut AppState, remaining: Duration, headline: &str, message: &str) {
-    let headline: SharedString = headline.into();
-    let message: SharedString = message.into();
-    let countdown = format_duration_mm_ss(remaining);
+/// 在与 `monitor` 对应的 winit 显示器上全屏；窗口尚未显示或找不到对应显示器时返回 false
+#[cfg(target_os = "linux")]
+fn fullscreen_on_monitor(window: &slint::Window, monitor: &MonitorRect) -> bool {
+    use slint::winit_030::winit::dpi::PhysicalPosition;
+    use slint::winit_030::winit::window::Fullscreen;
+    use slint::winit_030::WinitWindowAccessor;
+
+    window
+        .with_winit_window(|winit_window| {
+            let handle = winit_window.available_monitors().find(|handle| {
+                if monitor.name.is_empty() {
+                    handle.position() == PhysicalPosition::new(monitor.x, monitor.y)
+                } else {
+                    handle.name().as_deref() == Some(monitor.name.as_str())
+                }
+            })?;
+            winit_window.set_fullscreen(Some(Fullscreen::Borderless(Some(handle))));
+            Some(())
+        })
+        .flatten()
+        .is_some()
+}
 
-    // Always recreate overlay windows to handle monitor changes
-    state.overlay_windows.clear();
+fn create_overlay(
+    content: &OverlayContent,
+    monitor: Option<MonitorRect>,
+    countdown: &SharedString,
+) -> Option<OverlayWindowEntry> {
+    let overlay = RestOverlayWindow::new().ok()?;
+    content.look.apply(&overlay, monitor.as_ref());
+    overlay.set_headline(content.headline.clone());
+    overlay.set_message(content.message.clone());
+    overlay.set_exercise(content.exercise as i32);
+    overlay.set_waiting(content.waiting);
+    overlay.set_countdown(countdown.clone());
+    overlay.set_can_skip(content.can_skip);
+    overlay.set_key_hint(content.key_hint.clone());
+    let on_skip = content.handlers.on_skip.clone();
+    overlay.on_skip_rest(move || on_skip());
+    let on_returned = content.handlers.on_skip.clone();
+    overlay.on_returned(move || on_returned());
+    let on_shortcut = content.handlers.on_shortcut.clone();
+    overlay.on_shortcut(move |text, ctrl, alt, shift, meta| {
+        on_shortcut(KeyChord::from_event(&text, ctrl, alt, shift, meta))
+    });
 
-    #[cfg(target_os = "windows")]
-    {
-        for m in monitor_rects() {
-            let Ok(overlay) = RestOverlayWindow::new() else {
-                continue;
-            };
+    let entry = OverlayWindowEntry {
+        window: overlay,
+        monitor,
+    };
+    fit_overlay_to_monitor(&entry);
+    let _ = entry.window.window().show();
+    // 显示之后再摆放一次：部分平台在首次显示时会按默认位置放置窗口
+    fit_overlay_to_monitor(&entry);
+    entry.window.window().request_redraw();
+    Some(entry)
+}
 
-            let entry = OverlayWindowEntry {
-                window: overlay,
-                monitor: m,
-            };
-            fit_overlay_to_monitor(&entry);
-            state.overlay_windows.push(entry);
-        }
+fn show_rest_overlay(
+    state: &mut AppState,
+    remaining: Duration,
+    headline: &str,
+    message: &str,
+    exercise: Exercise,
+    look: OverlayLook,
+    handlers: OverlayHandlers,
+) {
+    // Always recreate overlay windows to handle monitor changes
+    hide_rest_overlay(state);
+    state.monitor_watch.poll();
+
+    let can_skip = state.strictness == Strictness::Relaxed;
+    let keys = &state.config.keys;
+    let mut hints = Vec::new();
+    if state.strictness != Strictness::Strict && KeyChord::parse(&keys.postpone).is_some() {
+        hints.push(format!(
+            "{} 推迟 {} 分钟",
+            keys.postpone,
+            POSTPONE_DURATION.as_secs() / 60
+        ));
     }
-
-    #[cfg(not(target_os = "windows"))]
-    {
-        if let Ok(overlay) = RestOverlayWindow::new() {
-            overlay.window().set_fullscreen(true);
-            state
-                .overlay_windows
-                .push(OverlayWindowEntry { window: overlay });
-        }
+    if can_skip && KeyChord::parse(&keys.skip).is_some() {
+        hints.push(format!("{} 跳过", keys.skip));
     }
 
-    // Fallback
-    if state.overlay_windows.is_empty() {
-        #[cfg(target_os = "windows")]
-        {
-            if let Ok(overlay) = RestOverlayWindow::new() {
-                let monitor = monitor_rects()
-                    .into_iter()
-                    .next()
-                    .unwrap_or_else(virtual_screen_rect);
-                let entry = OverlayWindowEntry {
-                    window: overlay,
-                    monitor,
-                };
-                fit_overlay_to_monitor(&entry);
-                state.overlay_windows.push(entry);
-            }
-        }
+    state.overlay_content = Some(OverlayContent {
+        headline: headline.into(),
+        message: message.into(),
+        exercise,
+        look,
+        waiting: false,
+        can_skip,
+        key_hint: hints.join("  ·  ").into(),
+        handlers,
+    });
+    reconcile_overlays(state, remaining);
+}
 
-        #[cfg(not(target_os = "windows"))]
-        {
-            if let Ok(overlay) = RestOverlayWindow::new() {
-                overlay.window().set_fullscreen(true);
-                state
-                    .overlay_windows
-                    .push(OverlayWindowEntry { window: overlay });
-            }
-        }
+/// 让遮罩与当前显示器一一对应：为新显示器创建遮罩，关闭已拔出的，重新摆放改变了的
+fn reconcile_overlays(state: &mut AppState, remaining: Duration) {
+    let Some(content) = &state.overlay_content else {
+        return;
+    };
+    let monitors = filter_outputs(
+        state.monitor_watch.current(),
+        &state.config.skipped_outputs(),
+    );
+    let existing: Vec<Option<MonitorRect>> = state
+        .overlay_windows
+        .iter()
+        .map(|entry| entry.monitor.clone())
+        .collect();
+    let plan = plan_overlays(&existing, &monitors);
+
+    for (index, monitor) in plan.refit {
+        let entry = &mut state.overlay_windows[index];
+        entry.monitor = Some(monitor);
+        fit_overlay_to_monitor(entry);
     }
 
-    for overlay in &state.overlay_windows {
-        overlay.window.set_headline(headline.clone());
-        overlay.window.set_message(message.clone());
-        overlay.window.set_countdown(countdown.clone());
-
-        #[cfg(target_os = "windows")]
-        fit_overlay_to_monitor(overlay);
-
-        let _ = overlay.window.window().show();
+    for index in plan.remove.into_iter().rev() {
+        let entry = state.overlay_windows.remove(index);
+        let _ = entry.window.window().hide();
+    }
 
-        #[cfg(target_os = "windows")]
-        fit_overlay_to_monitor(overlay);
+    let countdown = format_duration_mm_ss(remaining);
+    for monitor in plan.create {
+        if let Some(entry) = create_overlay(content, monitor, &countdown) {
+            state.overlay_windows.push(entry);
+        }
+    }
 
-        overlay.window.window().request_redraw();
+    // 完整内容只显示在主显示器上，其余显示器只显示调暗的倒计时
+    let detail = detail_monitor(&monitors);
+    for entry in &state.overlay_windows {
+        let primary = match (&entry.monitor, detail) {
+            (Some(monitor), Some(detail)) => monitor.same_output(detail),
+            _ => true,
+        };
+        entry.window.set_primary(primary);
     }
 }
 
@@ -706,6 +815,10 @@ fn update_rest_overlay(state: &mut AppState, remaining: Duration) {
         return;
     }
 
+    if state.monitor_watch.poll().is_some() {
+        reconcile_overlays(state, remaining);
+    }
+
     let countdown = format_duration_mm_ss(remaining);
     for overlay in &state.overlay_windows {
         overlay.window.set_countdown(countdown.clone());
@@ -722,257 +835,1283 @@ fn hide_rest_overlay(state: &mut AppState) {
         let _ = overlay.window.window().hide();
     }
     state.overlay_windows.clear();
+    state.overlay_content = None;
+}
+
+/// 遮罩上的操作都在回调之外执行，避免在窗口自身的回调里销毁它
+fn overlay_handlers(state: &Rc<RefCell<AppState>>, app: &MainWindow) -> OverlayHandlers {
+    let state_skip = Rc::downgrade(state);
+    let main_weak_skip = app.as_weak();
+    let on_skip = move || {
+        defer_overlay_action(state_skip.clone(), main_weak_skip.clone(), |state, app| {
+            if matches!(state.current_mode, Mode::Rest | Mode::AwaitingReturn) {
+                end_rest(state, Some(app), true);
+            }
+        });
+    };
+
+    let state_shortcut = Rc::downgrade(state);
+    let main_weak_shortcut = app.as_weak();
+    let on_shortcut = move |pressed: KeyChord| {
+        let Some(state) = state_shortcut.upgrade() else {
+            return false;
+        };
+        let action: fn(&mut AppState, &MainWindow) = {
+            let state = state.borrow();
+            let resting = state.current_mode == Mode::Rest;
+            match state.keymap.action(&pressed) {
+                Some(KeyAction::Skip) if resting && state.strictness == Strictness::Relaxed => {
+                    |state, app| {
+                        if state.current_mode == Mode::Rest {
+                            end_rest(state, Some(app), true);
+                        }
+                    }
+                }
+                Some(KeyAction::Postpone) if resting && state.strictness != Strictness::Strict => {
+                    postpone_rest
+                }
+                _ => return false,
+            }
+        };
+        defer_overlay_action(state_shortcut.clone(), main_weak_shortcut.clone(), action);
+        true
+    };
+
+    OverlayHandlers {
+        on_skip: Rc::new(on_skip),
+        on_shortcut: Rc::new(on_shortcut),
+    }
 }
 
-/// Load tray icon from embedded PNG data
-fn create_tray_icon() -> tray_icon::Icon {
-    // Embed the logo PNG at compile time
-    let png_data = include_bytes!("../assets/tray-icon.png");
-    let img = image::load_from_memory(png_data).expect("Failed to load tray icon");
-    let rgba = img.to_rgba8();
-    let (width, height) = rgba.dimensions();
-    tray_icon::Icon::from_rgba(rgba.into_raw(), width, height).expect("Failed to create tray icon")
+fn defer_overlay_action(
+    state: Weak<RefCell<AppState>>,
+    app: slint::Weak<MainWindow>,
+    action: fn(&mut AppState, &MainWindow),
+) {
+    Timer::single_shot(Duration::ZERO, move || {
+        let (Some(state), Some(app)) = (state.upgrade(), app.upgrade()) else {
+            return;
+        };
+        action(&mut state.borrow_mut(), &app);
+    });
 }
 
-fn main() -> Result<(), slint::PlatformError> {
-    #[cfg(target_os = "windows")]
-    enable_windows_per_monitor_dpi_awareness();
+/// 开始休息：决定提醒类型并显示全屏遮罩；无窗口模式下只发通知
+fn start_rest(state: &mut AppState, app: Option<&MainWindow>, state_rc: &Rc<RefCell<AppState>>) {
+    state.current_mode = Mode::Rest;
+    state.start_time = Instant::now();
+    state.eye_rest_count += 1;
+    let rest_duration = state.rest_duration;
+    let count = state.eye_rest_count;
+
+    // 判断是否需要额外提醒：走动 > 喝水（优先级）
+    state.current_rest_type = if state.walk_enabled && count.is_multiple_of(state.walk_interval) {
+        RestType::Walk
+    } else if state.water_enabled && count.is_multiple_of(state.water_interval) {
+        RestType::Water
+    } else {
+        RestType::EyeRest
+    };
+
+    let exercise = match state.current_rest_type {
+        RestType::Walk => Exercise::Stretch,
+        _ if rand::random() => Exercise::EyeCircle,
+        _ => Exercise::FarNear,
+    };
+
+    // 护眼提示始终显示（核心功能）
+    let pack = state.message_pack;
+    let (headline, mut message) = get_eye_rest_message(rest_duration.as_secs(), pack);
+
+    // 如果需要喝水或走动，附加额外提示
+    match state.current_rest_type {
+        RestType::Water => {
+            let (_, water_msg) = get_water_message(rest_duration.as_secs(), pack);
+            message = format!("{}\n\n💧 顺便提醒：{}", message, water_msg);
+        }
+        RestType::Walk => {
+            let (_, walk_msg) = get_walk_message(rest_duration.as_secs(), pack);
+            message = format!("{}\n\n🚶 顺便提醒：{}", message, walk_msg);
+        }
+        RestType::EyeRest => {}
+    }
+    announce(state, headline, &message);
+
+    let sound = match state.current_rest_type {
+        RestType::EyeRest => Sound::RestStart,
+        RestType::Water => Sound::Water,
+        RestType::Walk => Sound::Walk,
+    };
+    state.audio.rest_started(&state.config.sound, sound);
+
+    if let Some(app) = app {
+        // Hide main window during rest
+        if state.main_window_visible {
+            let _ = app.window().hide();
+        }
 
-    let main_window = MainWindow::new()?;
-    let state = Rc::new(RefCell::new(AppState::default()));
+        let look = OverlayLook::new(
+            &state.config.overlay,
+            &mut state.slideshow,
+            &app.global::<AppPalette>(),
+        );
+        show_rest_overlay(
+            state,
+            rest_duration,
+            headline,
+            &message,
+            exercise,
+            look,
+            overlay_handlers(state_rc, app),
+        );
+        app.set_status_text("Rest your eyes!".into());
+        app.set_time_display(format_duration_mm_ss(state.rest_duration));
+        app.set_progress(1.0);
+    }
+    run_hook(state, HookEvent::RestStarted);
+}
 
-    // Create system tray menu
-    let menu = Menu::new();
-    let show_item = MenuItem::new("显示窗口", true, None);
-    let quit_item = MenuItem::new("退出", true, None);
-    menu.append_items(&[&show_item, &quit_item]).unwrap();
+/// 休息时间到了但还不开始专注：遮罩变淡，等检测到键盘或鼠标操作
+fn await_return(state: &mut AppState, app: Option<&MainWindow>) {
+    state.current_mode = Mode::AwaitingReturn;
+    state.start_time = Instant::now();
+    state.audio.rest_ended(&state.config.sound);
+    count_break(state);
+    run_hook(state, HookEvent::RestEnded);
+
+    let headline: SharedString = "休息结束".into();
+    let message: SharedString = "动一下鼠标或按任意键，开始下一轮专注".into();
+    announce(state, &headline, &message);
+    if let Some(content) = &mut state.overlay_content {
+        content.headline = headline.clone();
+        content.message = message.clone();
+        content.exercise = Exercise::None;
+        content.waiting = true;
+    }
+    for overlay in &state.overlay_windows {
+        overlay.window.set_headline(headline.clone());
+        overlay.window.set_message(message.clone());
+        overlay.window.set_exercise(Exercise::None as i32);
+        overlay.window.set_waiting(true);
+    }
+    update_rest_overlay(state, Duration::ZERO);
 
-    // Create system tray icon
-    let _tray_icon = TrayIconBuilder::new()
-        .with_menu(Box::new(menu))
-        .with_tooltip("瞎了么")
-        .with_icon(create_tray_icon())
-        .build()
-        .expect("Failed to create tray icon");
+    if let Some(app) = app {
+        app.set_status_text("Welcome back?".into());
+        app.set_time_display(format_duration_mm_ss(state.work_duration));
+        app.set_progress(1.0);
+    }
+}
 
-    // Store menu item IDs for event handling
-    let show_item_id = show_item.id().clone();
-    let quit_item_id = quit_item.id().clone();
+/// 等待返回期间检测到输入（遮罩收不到时由系统空闲时间判断）
+fn user_returned(state: &AppState) -> bool {
+    platform::idle_time().is_some_and(|idle| idle < state.start_time.elapsed())
+}
 
-    // Sync initial settings UI
-    main_window.set_work_minutes((state.borrow().work_duration.as_secs() / 60) as i32);
-    main_window.set_rest_seconds(state.borrow().rest_duration.as_secs() as i32);
-    main_window.set_water_interval(state.borrow().water_interval as i32);
-    main_window.set_walk_interval(state.borrow().walk_interval as i32);
+/// 执行事件钩子，附带当前的计时器状态
+fn run_hook(state: &AppState, event: HookEvent) {
+    let rest_type = rest_type_name(state);
+    let payload = HookPayload {
+        event: event.name(),
+        time: Local::now().to_rfc3339(),
+        profile: state.config.active_profile.clone(),
+        rest_type,
+        remaining_seconds: state.remaining().as_secs(),
+        work_seconds: state.work_duration.as_secs(),
+        rest_seconds: state.rest_duration.as_secs(),
+        paused: state.is_paused,
+    };
+    if let Some(http) = &state.http {
+        http.publish(event.name(), &payload);
+    }
+    hooks::run(&state.config.hooks, event, payload);
+}
 
-    // Main timer for countdown logic
-    let timer = Timer::default();
-    let state_timer = state.clone();
-    let main_weak = main_window.as_weak();
+/// 休息中的提醒类型，钩子与 HTTP 接口中的写法
+fn rest_type_name(state: &AppState) -> Option<&'static str> {
+    (state.current_mode != Mode::Work).then_some(match state.current_rest_type {
+        RestType::EyeRest => "eye",
+        RestType::Water => "water",
+        RestType::Walk => "walk",
+    })
+}
 
-    timer.start(TimerMode::Repeated, Duration::from_millis(100), move || {
-        let mut state = state_timer.borrow_mut();
+/// 记一次完成的休息（跳过或推迟的不算），过了午夜重新计数
+fn count_break(state: &mut AppState) {
+    let today = Local::now().date_naive();
+    if state.breaks_today.0 != today {
+        state.breaks_today = (today, 0);
+    }
+    state.breaks_today.1 += 1;
+}
 
-        if state.is_paused {
-            let paused_for = state.last_tick.elapsed();
-            state.start_time += paused_for;
-            state.last_tick = Instant::now();
-            return;
-        }
+/// HTTP 接口展示的状态
+fn http_status(state: &AppState) -> http::Status {
+    let (date, count) = state.breaks_today;
+    http::Status {
+        mode: match state.current_mode {
+            Mode::Work => "work",
+            Mode::Rest => "rest",
+            Mode::AwaitingReturn => "awaiting_return",
+        },
+        paused: state.is_paused,
+        rest_type: rest_type_name(state),
+        profile: state.config.active_profile.clone(),
+        profiles: state
+            .config
+            .profiles
+            .iter()
+            .map(|profile| profile.name.clone())
+            .collect(),
+        remaining: state.remaining(),
+        counting: !state.is_paused && state.current_mode != Mode::AwaitingReturn,
+        breaks_today: if date == Local::now().date_naive() {
+            count
+        } else {
+            0
+        },
+    }
+}
 
-        state.last_tick = Instant::now();
-        let app = match main_weak.upgrade() {
-            Some(ui) => ui,
-            None => return,
-        };
+/// 无窗口模式下的提醒：打印到标准输出并发出桌面通知
+fn announce(state: &AppState, summary: &str, body: &str) {
+    if state.headless {
+        println!("{} {}", Local::now().format("%H:%M:%S"), summary);
+        notify::send(summary, body);
+    }
+}
 
-        let elapsed = state.start_time.elapsed();
-        let limit = match state.current_mode {
-            Mode::Work => state.work_duration,
-            Mode::Rest => state.rest_duration,
+/// 结束休息并回到工作计时；`skipped` 为提前结束
+fn end_rest(state: &mut AppState, app: Option<&MainWindow>, skipped: bool) {
+    // 等待返回时结束提示音已经播放过
+    if state.current_mode == Mode::Rest {
+        state.audio.rest_ended(&state.config.sound);
+        announce(state, "休息结束", "开始下一轮专注");
+        let event = if skipped {
+            HookEvent::RestSkipped
+        } else {
+            count_break(state);
+            HookEvent::RestEnded
         };
+        run_hook(state, event);
+    }
+    state.current_mode = Mode::Work;
+    state.start_time = Instant::now();
+    hide_rest_overlay(state);
+    run_hook(state, HookEvent::WorkStarted);
 
-        if elapsed >= limit {
-            state.start_time = Instant::now();
-            match state.current_mode {
-                Mode::Work => {
-                    state.current_mode = Mode::Rest;
-                    state.eye_rest_count += 1;
-                    let rest_duration = state.rest_duration;
-                    let count = state.eye_rest_count;
-
-                    // 判断是否需要额外提醒：走动 > 喝水（优先级）
-                    state.current_rest_type = if count % state.walk_interval == 0 {
-                        RestType::Walk
-                    } else if count % state.water_interval == 0 {
-                        RestType::Water
-                    } else {
-                        RestType::EyeRest
-                    };
-
-                    // 护眼提示始终显示（核心功能）
-                    let (headline, mut message) = get_eye_rest_message(rest_duration.as_secs());
-
-                    // 如果需要喝水或走动，附加额外提示
-                    match state.current_rest_type {
-                        RestType::Water => {
-                            let (_, water_msg) = get_water_message(rest_duration.as_secs());
-                            message = format!("{}\n\n💧 顺便提醒：{}", message, water_msg);
-                        }
-                        RestType::Walk => {
-                            let (_, walk_msg) = get_walk_message(rest_duration.as_secs());
-                            message = format!("{}\n\n🚶 顺便提醒：{}", message, walk_msg);
-                        }
-                        RestType::EyeRest => {}
-                    }
+    let Some(app) = app else {
+        return;
+    };
+    // Keep window minimized to tray if the user hid it.
+    // Only restore the window if it was visible before the rest started.
+    if state.main_window_visible {
+        let _ = app.window().show();
+    }
 
-                    // Hide main window during rest
-                    if state.main_window_visible {
-                        let _ = app.window().hide();
-                    }
+    app.set_status_text("Focus Time".into());
+    app.set_time_display(format_duration_mm_ss(state.work_duration));
+    app.set_progress(1.0);
+}
 
-                    show_rest_overlay(&mut state, rest_duration, headline, &message);
-                    app.set_status_text("Rest your eyes!".into());
-                    app.set_time_display(format_duration_mm_ss(state.rest_duration));
-                    app.set_progress(1.0);
-                }
-                Mode::Rest => {
-                    state.current_mode = Mode::Work;
-                    hide_rest_overlay(&mut state);
-
-                    // Keep window minimized to tray if the user hid it.
-                    // Only restore the window if it was visible before the rest started.
-                    if state.main_window_visible {
-                        let _ = app.window().show();
-                    }
+/// 推迟这次休息：关闭遮罩回到工作，几分钟后再提醒同一类休息
+fn postpone_rest(state: &mut AppState, app: &MainWindow) {
+    if state.current_mode != Mode::Rest {
+        return;
+    }
+    hide_rest_overlay(state);
+    state.audio.stop_ambient();
+    run_hook(state, HookEvent::RestSkipped);
+    // 下次休息沿用这次的提醒类型
+    state.eye_rest_count = state.eye_rest_count.saturating_sub(1);
+
+    state.current_mode = Mode::Work;
+    let until_rest = POSTPONE_DURATION.min(state.work_duration);
+    state.start_time = Instant::now()
+        .checked_sub(state.work_duration - until_rest)
+        .unwrap_or_else(Instant::now);
+
+    if state.main_window_visible {
+        let _ = app.window().show();
+    }
+    app.set_status_text("Focus Time".into());
+    app.set_time_display(format_duration_mm_ss(until_rest));
+    app.set_progress(until_rest.as_secs_f32() / state.work_duration.as_secs_f32());
+}
 
-                    app.set_status_text("Focus Time".into());
-                    app.set_time_display(format_duration_mm_ss(state.work_duration));
+/// 工作中重新计时；休息中提前结束休息（严格模式下不允许）
+fn skip_or_reset(state: &mut AppState, app: Option<&MainWindow>) {
+    if !state.can_skip() {
+        return;
+    }
+
+    state.start_time = Instant::now();
+    match state.current_mode {
+        Mode::Work => {
+            if let Some(app) = app {
+                app.set_time_display(format_duration_mm_ss(state.work_duration));
+                app.set_progress(1.0);
+            }
+            run_hook(state, HookEvent::WorkStarted);
+        }
+        Mode::Rest | Mode::AwaitingReturn => {
+            state.main_window_visible = !state.headless;
+            end_rest(state, app, true);
+        }
+    }
+}
+
+/// 暂停或继续；`until` 为定时暂停的结束时间
+fn set_paused(
+    state: &mut AppState,
+    app: Option<&MainWindow>,
+    paused: bool,
+    until: Option<DateTime<Local>>,
+) {
+    // 结算上次 tick 之后的时间：暂停期间的部分不计入倒计时
+    if state.is_paused {
+        state.start_time += state.last_tick.elapsed();
+    }
+    state.last_tick = Instant::now();
+
+    let changed = state.is_paused != paused;
+    state.is_paused = paused;
+    state.pause_until = if paused { until } else { None };
+    if let Some(app) = app {
+        app.set_is_paused(paused);
+    }
+    if changed {
+        run_hook(
+            state,
+            if paused {
+                HookEvent::Paused
+            } else {
+                HookEvent::Resumed
+            },
+        );
+    }
+}
+
+fn show_main_window(state: &mut AppState, app: &MainWindow) {
+    let _ = app.window().show();
+    state.main_window_visible = true;
+    remember_main_window(state, app);
+    // 继续隐藏时停下的迷你模式轮询；从未开始时不起作用
+    if state.config.compact.enabled {
+        state.compact_hover.timer.restart();
+    }
+}
+
+/// 隐藏到托盘；先记下位置，隐藏后的窗口取不到可靠的位置。没有托盘时改为最小化，
+/// 否则窗口就再也找不回来了
+fn hide_main_window(state: &mut AppState, app: &MainWindow) {
+    if !state.tray_enabled {
+        app.window().set_minimized(true);
+        return;
+    }
+    state.main_window_visible = false;
+    remember_main_window(state, app);
+    let _ = app.window().hide();
+    // 窗口不在屏幕上时不必每 100ms 查询鼠标位置
+    state.compact_hover.timer.stop();
+}
+
+/// 记下主窗口在当前显示器布局下的位置、大小与是否可见，有变化时保存；
+/// 迷你模式下只更新小窗的位置，保留卡片的位置
+fn remember_main_window(state: &mut AppState, app: &MainWindow) {
+    state.monitor_watch.poll();
+    let layout = window_placement::layout_key(state.monitor_watch.current());
+    let window = app.window();
+    let compact = state.config.compact.enabled;
+    let settings = &mut state.config.window;
+    let previous = settings.placements.get(&layout).copied();
+    let mut placement = previous.unwrap_or(WindowPlacement {
+        x: 0,
+        y: 0,
+        width: 0,
+        height: 0,
+        visible: true,
+        compact: None,
+    });
+    placement.visible = state.main_window_visible;
+    // 休息期间主窗口被临时隐藏，位置不可靠，只更新可见性
+    if window.is_visible() || previous.is_none() {
+        let (position, size) = (window.position(), window.size());
+        if compact {
+            placement.compact = Some([position.x, position.y]);
+        } else {
+            placement.x = position.x;
+            placement.y = position.y;
+            placement.width = size.width;
+            placement.height = size.height;
+        }
+    }
+    if previous == Some(placement) && settings.last_layout.as_deref() == Some(layout.as_str()) {
+        return;
+    }
+    settings.placements.insert(layout.clone(), placement);
+    settings.last_layout = Some(layout);
+    let _ = state.config.save();
+}
+
+/// 启动时恢复迷你模式与窗口位置；返回是否显示主窗口
+fn restore_main_window(state: &AppState, app: &MainWindow) -> bool {
+    app.set_compact(state.config.compact.enabled);
+    let visible = place_main_window(state, app).unwrap_or(true);
+    visible && !state.config.window.start_minimized
+}
+
+/// 把主窗口放到当前显示器布局下、当前模式记住的位置，所在显示器已不存在时挪回屏幕内；
+/// 返回记录中窗口是否可见，没有记录时不移动
+fn place_main_window(state: &AppState, app: &MainWindow) -> Option<bool> {
+    let monitors = state.monitor_watch.current();
+    let placement = state
+        .config
+        .window
+        .placement(&window_placement::layout_key(monitors))?;
+    let card = WorkArea {
+        x: placement.x,
+        y: placement.y,
+        width: placement.width,
+        height: placement.height,
+    };
+    let (x, y) = if state.config.compact.enabled {
+        let (width, height) = compact_size(app);
+        match placement.compact {
+            Some([x, y]) => window_placement::clamp_position(
+                WorkArea {
+                    x,
+                    y,
+                    width,
+                    height,
+                },
+                monitors,
+            ),
+            None => window_placement::top_right_corner(
+                card,
+                (width, height),
+                monitors,
+                (COMPACT_MARGIN * app.window().scale_factor()) as u32,
+            ),
+        }
+    } else {
+        window_placement::clamp_position(card, monitors)
+    };
+    app.window()
+        .set_position(slint::PhysicalPosition::new(x, y));
+    Some(placement.visible)
+}
+
+/// 迷你小窗的物理像素尺寸
+fn compact_size(app: &MainWindow) -> (u32, u32) {
+    let scale = app.window().scale_factor();
+    (
+        (COMPACT_SIZE.0 * scale).round() as u32,
+        (COMPACT_SIZE.1 * scale).round() as u32,
+    )
+}
+
+/// 切换迷你模式：先记下当前模式的位置，再移到另一模式记住的位置
+fn set_compact(state_rc: &Rc<RefCell<AppState>>, app: &MainWindow, compact: bool) {
+    let mut state = state_rc.borrow_mut();
+    if state.config.compact.enabled == compact {
+        return;
+    }
+    remember_main_window(&mut state, app);
+    state.config.compact.enabled = compact;
+    app.set_compact(compact);
+    place_main_window(&state, app);
+    let _ = state.config.save();
+    drop(state);
+
+    if compact {
+        start_compact_hover(state_rc, app);
+    } else {
+        let mut state = state_rc.borrow_mut();
+        state.compact_hover.timer.stop();
+        set_click_through(app, &mut state.compact_hover, false);
+    }
+}
+
+/// 拖动迷你小窗结束时贴到附近的屏幕边缘，并更新刚记下的位置
+///
+/// 窗口管理器处理移动需要时间，这里直接保存算出的位置，不再从窗口读取。
+fn snap_compact_window(state: &mut AppState, app: &MainWindow) {
+    let window = app.window();
+    let (position, size) = (window.position(), window.size());
+    let distance = (state.config.compact.snap_distance as f32 * window.scale_factor()) as u32;
+    let (x, y) = window_placement::snap_to_edges(
+        WorkArea {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        },
+        state.monitor_watch.current(),
+        distance,
+    );
+    if (x, y) == (position.x, position.y) {
+        return;
+    }
+    window.set_position(slint::PhysicalPosition::new(x, y));
+    let layout = window_placement::layout_key(state.monitor_watch.current());
+    if let Some(placement) = state.config.window.placements.get_mut(&layout) {
+        placement.compact = Some([x, y]);
+        let _ = state.config.save();
+    }
+}
+
+/// 迷你模式的点击穿透：轮询鼠标位置，停留足够久才让小窗接收点击
+#[derive(Default)]
+struct CompactHover {
+    timer: Timer,
+    /// 第一次进入迷你模式时才连接
+    cursor: Option<CursorTracker>,
+    since: Option<Instant>,
+    click_through: bool,
+}
+
+fn start_compact_hover(state_rc: &Rc<RefCell<AppState>>, app: &MainWindow) {
+    let mut state = state_rc.borrow_mut();
+    if !state.config.compact.click_through || state.headless {
+        return;
+    }
+    let hover = &mut state.compact_hover;
+    let cursor = hover.cursor.get_or_insert_with(CursorTracker::new);
+    // 取不到鼠标位置（Wayland）时无法判断停留，小窗保持可点击
+    if cursor.position().is_none() {
+        return;
+    }
+    let state_weak = Rc::downgrade(state_rc);
+    let app_weak = app.as_weak();
+    hover
+        .timer
+        .start(slint::TimerMode::Repeated, COMPACT_HOVER_POLL, move || {
+            if let (Some(state), Some(app)) = (state_weak.upgrade(), app_weak.upgrade()) {
+                poll_compact_hover(&mut state.borrow_mut(), &app);
+            }
+        });
+}
+
+fn poll_compact_hover(state: &mut AppState, app: &MainWindow) {
+    let window = app.window();
+    let dragging = state.drag_anchor_window_pos.is_some();
+    let hover = &mut state.compact_hover;
+    if !window.is_visible() {
+        // 重新显示时窗口可能被重建，恢复为默认的可点击状态
+        hover.since = None;
+        hover.click_through = false;
+        return;
+    }
+    let Some((x, y)) = hover.cursor.as_ref().and_then(CursorTracker::position) else {
+        return;
+    };
+    let (position, size) = (window.position(), window.size());
+    let inside = (position.x..position.x + size.width as i32).contains(&x)
+        && (position.y..position.y + size.height as i32).contains(&y);
+    let interactive = match (inside, hover.since) {
+        _ if dragging => true,
+        (false, _) => {
+            hover.since = None;
+            false
+        }
+        (true, None) => {
+            hover.since = Some(Instant::now());
+            false
+        }
+        (true, Some(since)) => since.elapsed() >= COMPACT_HOVER_DWELL,
+    };
+    set_click_through(app, hover, !interactive);
+}
+
+fn set_click_through(app: &MainWindow, hover: &mut CompactHover, enabled: bool) {
+    use slint::winit_030::WinitWindowAccessor;
+
+    if hover.click_through == enabled {
+        return;
+    }
+    hover.click_through = enabled;
+    app.window().with_winit_window(|winit_window| {
+        let _ = winit_window.set_cursor_hittest(!enabled);
+    });
+}
+
+/// 下一个本地午夜，用于「暂停到明天」
+fn next_local_midnight() -> Option<DateTime<Local>> {
+    let tomorrow = Local::now().date_naive().succ_opt()?;
+    tomorrow
+        .and_hms_opt(0, 0, 0)?
+        .and_local_timezone(Local)
+        .earliest()
+}
+
+/// 推进倒计时：到点时切换工作/休息，否则刷新界面上的剩余时间
+fn advance_countdown(
+    state: &mut AppState,
+    app: Option<&MainWindow>,
+    state_rc: &Rc<RefCell<AppState>>,
+) {
+    let elapsed = state.start_time.elapsed();
+    let limit = state.current_limit();
+
+    if state.current_mode == Mode::AwaitingReturn {
+        if user_returned(state) {
+            end_rest(state, app, false);
+        } else {
+            // 遮罩上显示已经离开了多久
+            update_rest_overlay(state, elapsed);
+        }
+        return;
+    }
+
+    if elapsed >= limit {
+        match state.current_mode {
+            Mode::Work if state.skip_next_rest => {
+                state.skip_next_rest = false;
+                state.start_time = Instant::now();
+                if let Some(app) = app {
+                    app.set_status_text("Focus Time".into());
+                    app.set_time_display(format_duration_mm_ss(state.work_duration));
                     app.set_progress(1.0);
                 }
+                run_hook(state, HookEvent::RestSkipped);
+                run_hook(state, HookEvent::WorkStarted);
+            }
+            Mode::Work => start_rest(state, app, state_rc),
+            // 无窗口模式下没有遮罩接收输入，取不到空闲时间（Wayland）就不等了
+            Mode::Rest
+                if state.await_return && (!state.headless || platform::idle_time().is_some()) =>
+            {
+                await_return(state, app)
             }
+            Mode::Rest | Mode::AwaitingReturn => end_rest(state, app, false),
+        }
+        return;
+    }
+
+    let remaining = limit - elapsed;
+    if let Some(app) = app {
+        app.set_time_display(format_duration_mm_ss(remaining));
+        app.set_progress(1.0 - (elapsed.as_secs_f32() / limit.as_secs_f32()));
+    }
+
+    match state.current_mode {
+        Mode::Work => check_warning(state, remaining),
+        Mode::Rest => update_rest_overlay(state, remaining),
+        Mode::AwaitingReturn => {}
+    }
+}
+
+/// `warning_seconds` 表示的提前量；为 0、不短于工作时长或下次休息会被跳过时为 None
+fn warning_lead(state: &AppState) -> Option<Duration> {
+    let lead = Duration::from_secs(state.config.hooks.warning_seconds);
+    (!lead.is_zero() && lead < state.work_duration && !state.skip_next_rest).then_some(lead)
+}
+
+/// 剩余时间第一次进入提前量时触发 warning；重新计时、换方案后剩余时间变长，再次生效
+fn check_warning(state: &mut AppState, remaining: Duration) {
+    match warning_lead(state) {
+        Some(lead) if remaining <= lead => {
+            if !state.warned {
+                state.warned = true;
+                run_hook(state, HookEvent::Warning);
+            }
+        }
+        _ => state.warned = false,
+    }
+}
+
+/// 距离下一次可观察变化的时间
+///
+/// 主窗口或遮罩可见时对齐到倒计时的下一个整秒；只剩托盘时对齐到下一个整分钟
+/// （托盘图标按分钟刷新），同时不错过阶段切换、定时暂停结束和按分钟生效的自动切换规则。
+fn next_tick_delay(state: &AppState) -> Duration {
+    let now = Local::now();
+    let until_wall_minute = Duration::from_secs(60 - now.second() as u64)
+        .saturating_sub(Duration::from_nanos(now.nanosecond() as u64));
+    let mut delay = until_wall_minute.min(MAX_TICK_INTERVAL);
+
+    if let Some(until) = state.pause_until {
+        delay = delay.min((until - now).to_std().unwrap_or_default());
+    }
+
+    if state.current_mode == Mode::AwaitingReturn {
+        // 没有剩余时间可对齐：每秒检查一次是否有输入，并刷新离开时长
+        let elapsed = state.start_time.elapsed().as_nanos();
+        let period = Duration::from_secs(1).as_nanos();
+        delay = delay.min(Duration::from_nanos((period - elapsed % period) as u64));
+    } else if !state.is_paused {
+        let visible = state.main_window_visible || state.current_mode == Mode::Rest;
+        let period = if visible {
+            Duration::from_secs(1)
         } else {
-            let remaining = limit - elapsed;
-            let secs_remaining = remaining.as_secs();
-            let mins = secs_remaining / 60;
-            let secs = secs_remaining % 60;
+            Duration::from_secs(60)
+        };
+        // remaining 跨过下一个 period 整数倍的时刻；阶段切换（remaining = 0）也落在其中
+        let remaining = state.remaining().as_nanos();
+        let until_boundary = Duration::from_nanos((remaining % period.as_nanos()) as u64);
+        delay = delay.min(until_boundary);
+
+        // 不错过 warning 钩子
+        if state.current_mode == Mode::Work && !state.warned {
+            if let Some(until_warning) = warning_lead(state)
+                .and_then(|lead| state.remaining().checked_sub(lead))
+                .filter(|until| !until.is_zero())
+            {
+                delay = delay.min(until_warning);
+            }
+        }
+    }
 
-            app.set_time_display(SharedString::from(format!("{:02}:{:02}", mins, secs)));
+    delay + TICK_SLACK
+}
 
-            let progress = 1.0 - (elapsed.as_secs_f32() / limit.as_secs_f32());
-            app.set_progress(progress);
+/// 托盘表头与提示中显示的状态文字
+fn tray_status(state: &AppState, dark_mode: bool) -> TrayStatus {
+    let remaining = state.remaining();
+    let countdown = format_duration_mm_ss(remaining);
+    let summary = match (state.is_paused, state.current_mode) {
+        (true, _) => match state.pause_until {
+            Some(until) => format!("已暂停至 {}", until.format("%m-%d %H:%M")),
+            None => format!("已暂停 {}", countdown),
+        },
+        (false, Mode::Work) if state.skip_next_rest => {
+            format!("专注中 {}（跳过下次休息）", countdown)
+        }
+        (false, Mode::Work) => format!("专注中 {}", countdown),
+        (false, Mode::Rest) => format!("休息中 {}", countdown),
+        (false, Mode::AwaitingReturn) => "休息结束，等你回来".to_string(),
+    };
+
+    TrayStatus {
+        summary,
+        paused: state.is_paused,
+        resting: state.current_mode != Mode::Work,
+        can_skip: state.can_skip(),
+        active_profile: state.config.active_index(),
+        dark_mode,
+        phase: match (state.is_paused, state.current_mode) {
+            (true, _) => IconPhase::Paused,
+            (false, Mode::Work) => IconPhase::Working,
+            (false, Mode::Rest | Mode::AwaitingReturn) => IconPhase::Resting,
+        },
+        // 按整秒计算，避免每个 tick 都判定为状态变化
+        progress: remaining.as_secs() as f32 / state.current_limit().as_secs().max(1) as f32,
+        minutes_left: remaining.as_secs().div_ceil(60),
+    }
+}
 
-            if state.current_mode == Mode::Rest {
-                update_rest_overlay(&mut state, remaining);
+fn handle_tray_action(
+    state_rc: &Rc<RefCell<AppState>>,
+    app: Option<&MainWindow>,
+    action: TrayAction,
+) {
+    let mut state = state_rc.borrow_mut();
+    match action {
+        TrayAction::ShowWindow => {
+            if let Some(app) = app {
+                show_main_window(&mut state, app);
             }
         }
-    });
+        TrayAction::TogglePause => {
+            let paused = !state.is_paused;
+            set_paused(&mut state, app, paused, None);
+        }
+        TrayAction::Skip => skip_or_reset(&mut state, app),
+        TrayAction::RestNow => {
+            if state.current_mode == Mode::Work {
+                set_paused(&mut state, app, false, None);
+                start_rest(&mut state, app, state_rc);
+            }
+        }
+        TrayAction::PauseFor(duration) => {
+            let until = chrono::Duration::from_std(duration)
+                .ok()
+                .map(|duration| Local::now() + duration);
+            set_paused(&mut state, app, true, until);
+        }
+        TrayAction::PauseUntilTomorrow => set_paused(&mut state, app, true, next_local_midnight()),
+        TrayAction::SelectProfile(index) => switch_profile(&mut state, app, index, true),
+        TrayAction::ToggleTheme => {
+            if let Some(app) = app {
+                toggle_theme(&mut state, app);
+            }
+        }
+        TrayAction::Quit => {
+            if let Some(app) = app {
+                remember_main_window(&mut state, app);
+            }
+            slint::quit_event_loop().ok();
+        }
+    }
+}
+
+fn set_theme_mode(state: &mut AppState, app: &MainWindow, mode: ThemeMode) {
+    state.config.theme.mode = mode;
+    app.global::<AppPalette>()
+        .set_theme_mode(mode.index() as i32);
+}
+
+fn set_accessibility(state: &mut AppState, app: &MainWindow, high_contrast: bool, text_scale: f32) {
+    state.config.accessibility.high_contrast = high_contrast;
+    state.config.accessibility.text_scale = text_scale.clamp(1.0, 1.5);
+    let palette = app.global::<AppPalette>();
+    palette.set_high_contrast(high_contrast);
+    palette.set_text_scale(state.config.accessibility.text_scale);
+}
+
+/// 主窗口和托盘的主题按钮：在浅色与深色之间切换并保存，不再跟随系统
+fn toggle_theme(state: &mut AppState, app: &MainWindow) {
+    let mode = if app.global::<AppPalette>().get_dark_mode() {
+        ThemeMode::Light
+    } else {
+        ThemeMode::Dark
+    };
+    set_theme_mode(state, app, mode);
+    let _ = state.config.save();
+}
+
+/// 工作中切换是否跳过下一次休息；休息中直接结束这次休息
+fn toggle_skip_next_rest(state: &mut AppState, app: Option<&MainWindow>) {
+    match state.current_mode {
+        Mode::Work => {
+            state.skip_next_rest = !state.skip_next_rest;
+            if let Some(app) = app {
+                app.set_status_text(if state.skip_next_rest {
+                    "Next rest skipped".into()
+                } else {
+                    "Focus Time".into()
+                });
+            }
+        }
+        Mode::Rest | Mode::AwaitingReturn => skip_or_reset(state, app),
+    }
+}
+
+fn handle_hotkey_action(
+    state_rc: &Rc<RefCell<AppState>>,
+    app: Option<&MainWindow>,
+    action: HotkeyAction,
+) {
+    let tray_action = match action {
+        HotkeyAction::TogglePause => TrayAction::TogglePause,
+        HotkeyAction::RestNow => TrayAction::RestNow,
+        HotkeyAction::ShowWindow => TrayAction::ShowWindow,
+        HotkeyAction::SkipNextRest => {
+            toggle_skip_next_rest(&mut state_rc.borrow_mut(), app);
+            return;
+        }
+    };
+    handle_tray_action(state_rc, app, tray_action);
+}
 
-    // Timer for polling tray events
-    let tray_timer = Timer::default();
-    let state_tray = state.clone();
-    let main_weak_tray = main_window.as_weak();
-    let show_id = show_item_id.clone();
-    let quit_id = quit_item_id.clone();
-
-    tray_timer.start(TimerMode::Repeated, Duration::from_millis(50), move || {
-        // Handle menu events
-        if let Ok(event) = MenuEvent::receiver().try_recv() {
-            if event.id == show_id {
-                // Show main window
-                if let Some(app) = main_weak_tray.upgrade() {
-                    let _ = app.window().show();
-                    state_tray.borrow_mut().main_window_visible = true;
+/// 无窗口模式下从标准输入收到的命令
+fn handle_command(state_rc: &Rc<RefCell<AppState>>, app: Option<&MainWindow>, command: Command) {
+    let paused = state_rc.borrow().is_paused;
+    let tray_action = match command {
+        Command::Pause(Some(duration)) => TrayAction::PauseFor(duration),
+        Command::Pause(None) if !paused => TrayAction::TogglePause,
+        Command::Resume if paused => TrayAction::TogglePause,
+        Command::Pause(None) | Command::Resume => return,
+        Command::Skip => TrayAction::Skip,
+        Command::RestNow => TrayAction::RestNow,
+        Command::Quit => TrayAction::Quit,
+        Command::Status => {
+            println!("{}", tray_status(&state_rc.borrow(), false).summary);
+            return;
+        }
+        Command::Profile(target) => {
+            let state = state_rc.borrow();
+            let names = state
+                .config
+                .profiles
+                .iter()
+                .map(|profile| profile.name.as_str());
+            match headless::profile_index(names, &target) {
+                Some(index) => TrayAction::SelectProfile(index),
+                None => {
+                    eprintln!("Unknown profile: {}", target);
+                    return;
                 }
-            } else if event.id == quit_id {
-                // Quit application
-                slint::quit_event_loop().ok();
             }
         }
+    };
+    handle_tray_action(state_rc, app, tray_action);
+}
+
+/// 把当前方案同步到设置面板
+fn sync_settings_ui(app: &MainWindow, state: &AppState) {
+    let names: Vec<SharedString> = state
+        .config
+        .profiles
+        .iter()
+        .map(|profile| profile.name.as_str().into())
+        .collect();
+    app.set_profile_names(ModelRc::new(VecModel::from(names)));
+    app.set_active_profile(state.config.active_index() as i32);
+
+    app.set_work_duration(format_duration(state.work_duration).into());
+    app.set_rest_seconds(state.rest_duration.as_secs() as i32);
+    app.set_water_interval(state.water_interval as i32);
+    app.set_walk_interval(state.walk_interval as i32);
+    app.set_overlay_style(state.config.overlay.style.index() as i32);
+    app.set_start_minimized(state.config.window.start_minimized);
+    app.set_launch_at_login(autostart::is_enabled());
+    app.set_compact_opacity(state.config.compact.opacity);
+
+    let hotkeys = &state.config.hotkeys;
+    app.set_hotkey_pause(hotkeys.pause.as_str().into());
+    app.set_hotkey_rest_now(hotkeys.rest_now.as_str().into());
+    app.set_hotkey_skip_next(hotkeys.skip_next.as_str().into());
+    app.set_hotkey_show_window(hotkeys.show_window.as_str().into());
+}
+
+/// 切换到指定方案；`persist` 为用户自己选的方案，保存并取代规则结束后要恢复的方案，
+/// 自动切换规则的切换只在内存中生效
+fn switch_profile(state: &mut AppState, app: Option<&MainWindow>, index: usize, persist: bool) {
+    let Some(profile) = state.config.profiles.get(index) else {
+        return;
+    };
+    state.config.active_profile = profile.name.clone();
+    if persist {
+        state.config.profile_before_schedule = None;
+    }
+    state.load_active_profile();
+
+    if state.current_mode == Mode::Work {
+        state.start_time = Instant::now();
+        state.last_tick = Instant::now();
+    }
+
+    if let Some(app) = app {
+        if state.current_mode == Mode::Work {
+            app.set_status_text("Focus Time".into());
+            app.set_time_display(format_duration_mm_ss(state.work_duration));
+            app.set_progress(1.0);
+        }
+        sync_settings_ui(app, state);
+    }
+    if persist {
+        let _ = state.config.save();
+    }
+}
 
-        // Handle tray icon click events
-        if let Ok(TrayIconEvent::Click {
-            button: tray_icon::MouseButton::Left,
-            ..
-        }) = TrayIconEvent::receiver().try_recv()
+fn main() -> Result<(), slint::PlatformError> {
+    let options = Options::from_env();
+    if let Some(path) = &options.config {
+        Config::use_path(path.clone());
+    }
+    // The headless platform must be in place before the first component is created
+    if options.headless {
+        headless::install_platform()?;
+    }
+    platform::enable_dpi_awareness();
+
+    let config = Config::load();
+    let profile = options.profile.as_deref().map(|name| {
+        config.profile_index(name).unwrap_or_else(|| {
+            let names: Vec<&str> = config.profiles.iter().map(|p| p.name.as_str()).collect();
+            eprintln!(
+                "Unknown profile: {} (available: {})",
+                name,
+                names.join(", ")
+            );
+            std::process::exit(2);
+        })
+    });
+
+    // 无窗口模式不创建任何窗口，计时逻辑在没有主窗口时只更新状态
+    let main_window = if options.headless {
+        None
+    } else {
+        Some(MainWindow::new()?)
+    };
+    let state = Rc::new(RefCell::new(AppState::new(config, options.headless)));
+
+    // Command-line overrides: the profile is remembered, durations and pause only apply to this run
+    {
+        let mut state = state.borrow_mut();
+        state.tray_enabled = options.tray();
+        state.work_override = options.work;
+        state.rest_override = options.rest;
+        match profile {
+            Some(index) => switch_profile(&mut state, main_window.as_ref(), index, true),
+            None => state.load_active_profile(),
+        }
+        // 第一轮专注从启动时开始
+        run_hook(&state, HookEvent::WorkStarted);
+        if options.start_paused {
+            set_paused(&mut state, main_window.as_ref(), true, None);
+        }
+    }
+
+    // Create system tray icon and menu
+    let tray = options.tray().then(|| {
+        let state = state.borrow();
+        let names: Vec<String> = state
+            .config
+            .profiles
+            .iter()
+            .map(|p| p.name.clone())
+            .collect();
+        Rc::new(Tray::new(
+            &names,
+            state.config.active_index(),
+            state.config.tray_icon,
+        ))
+    });
+
+    // Main timer for countdown logic: wakes only when something observable changes
+    let ticker = Ticker::new();
+    let state_timer = state.clone();
+    let main_weak = main_window.as_ref().map(MainWindow::as_weak);
+    let tray_timer_sync = tray.clone();
+
+    ticker.start(move || {
+        let app = match main_weak.as_ref().map(slint::Weak::upgrade) {
+            Some(None) => return MAX_TICK_INTERVAL,
+            app => app.flatten(),
+        };
+        let app = app.as_ref();
+        let mut state = state_timer.borrow_mut();
+
+        // 按时间规则自动切换方案（暂停时也生效）
+        if state
+            .last_schedule_check
+            .is_none_or(|checked| checked.elapsed() >= SCHEDULE_CHECK_INTERVAL)
         {
-            // Left click: show main window
-            if let Some(app) = main_weak_tray.upgrade() {
-                let _ = app.window().show();
-                state_tray.borrow_mut().main_window_visible = true;
+            state.last_schedule_check = Some(Instant::now());
+            if let Some(index) = state.scheduled_profile() {
+                switch_profile(&mut state, app, index, false);
+            }
+        }
+
+        if state.is_paused {
+            let paused_for = state.last_tick.elapsed();
+            state.start_time += paused_for;
+            state.last_tick = Instant::now();
+
+            // 定时暂停到期后自动继续
+            if state.pause_until.is_some_and(|until| Local::now() >= until) {
+                set_paused(&mut state, app, false, None);
             }
+        } else {
+            state.last_tick = Instant::now();
+            advance_countdown(&mut state, app, &state_timer);
+        }
+
+        if let Some(tray) = &tray_timer_sync {
+            let dark_mode = app.is_some_and(|app| app.global::<AppPalette>().get_dark_mode());
+            tray.sync(tray_status(&state, dark_mode));
         }
+        if let Some(http) = &state.http {
+            http.update(http_status(&state));
+        }
+        next_tick_delay(&state)
+    });
+
+    // Tray events are pushed into the Slint event loop as they arrive; there is no tray in headless mode
+    if let (Some(tray_events), Some(main_window)) = (tray, &main_window) {
+        let state_tray = state.clone();
+        let main_weak_tray = main_window.as_weak();
+        let ticker_tray = ticker.clone();
+        tray::install_event_handler(move |event| {
+            let Some(app) = main_weak_tray.upgrade() else {
+                return;
+            };
+            match event {
+                TrayEvent::Menu(event) => {
+                    // 勾选项被点击时会自行切换勾选状态，强制下次按实际状态刷新
+                    tray_events.invalidate();
+                    if let Some(action) = tray_events.action(&event.id) {
+                        handle_tray_action(&state_tray, Some(&app), action);
+                    }
+                }
+                // Left click: show main window
+                TrayEvent::Icon(TrayIconEvent::Click {
+                    button: MouseButton::Left,
+                    button_state: MouseButtonState::Up,
+                    ..
+                }) => show_main_window(&mut state_tray.borrow_mut(), &app),
+                TrayEvent::Icon(_) => {}
+            }
+            ticker_tray.kick();
+        });
+    }
+
+    // Commands from standard input (headless mode) and the HTTP interface
+    let http_enabled = state.borrow().config.http.enabled;
+    if options.headless || http_enabled {
+        let state_command = state.clone();
+        let main_weak_command = main_window.as_ref().map(MainWindow::as_weak);
+        let ticker_command = ticker.clone();
+        headless::install_command_handler(move |command| {
+            let app = main_weak_command.as_ref().and_then(slint::Weak::upgrade);
+            handle_command(&state_command, app.as_ref(), command);
+            ticker_command.kick();
+        });
+    }
+    if options.headless {
+        headless::read_commands();
+    }
+    if http_enabled {
+        let port = state.borrow().config.http.port;
+        let status = http_status(&state.borrow());
+        match HttpServer::start(port, status) {
+            Ok(server) => state.borrow_mut().http = Some(server),
+            Err(e) => eprintln!("Failed to start HTTP server on port {}: {}", port, e),
+        }
+    }
+
+    // Global hotkeys are forwarded from their listener threads the same way
+    let state_hotkey = state.clone();
+    let main_weak_hotkey = main_window.as_ref().map(MainWindow::as_weak);
+    let ticker_hotkey = ticker.clone();
+    hotkeys::install_event_handler(move |action| {
+        let app = main_weak_hotkey.as_ref().and_then(slint::Weak::upgrade);
+        handle_hotkey_action(&state_hotkey, app.as_ref(), action);
+        ticker_hotkey.kick();
     });
 
+    if let Some(main_window) = &main_window {
+        connect_main_window(main_window, &state, &ticker);
+
+        // Show main window where it was left for this monitor layout, unless it starts in the tray.
+        // Without a tray it starts minimized instead.
+        let start_visible = restore_main_window(&state.borrow(), main_window) && !options.minimized;
+        let shown = start_visible || !options.tray();
+        state.borrow_mut().main_window_visible = shown;
+        if shown {
+            main_window.show()?;
+            if !start_visible {
+                main_window.window().set_minimized(true);
+            }
+        }
+        if state.borrow().config.compact.enabled {
+            start_compact_hover(&state, main_window);
+            // 启动时藏在托盘里，等显示窗口时再开始轮询
+            if !shown {
+                state.borrow().compact_hover.timer.stop();
+            }
+        }
+    } else {
+        state.borrow_mut().main_window_visible = false;
+    }
+
+    // Use run_event_loop_until_quit which doesn't exit when all windows are hidden.
+    // The timers we created above will keep the event loop alive.
+    slint::run_event_loop_until_quit()?;
+
+    Ok(())
+}
+
+/// 主窗口的主题、设置面板与各个回调
+fn connect_main_window(
+    main_window: &MainWindow,
+    state: &Rc<RefCell<AppState>>,
+    ticker: &Rc<Ticker>,
+) {
+    // Theme: configured colors and mode, following the system light/dark preference,
+    // plus the high-contrast palette and text scale
+    {
+        let state = state.borrow();
+        let palette = main_window.global::<AppPalette>();
+        theme::apply_colors(&palette, &state.config.theme);
+        palette.set_theme_mode(state.config.theme.mode.index() as i32);
+        palette.set_system_dark(theme::system_prefers_dark().unwrap_or(false));
+        palette.set_high_contrast(state.config.accessibility.high_contrast);
+        palette.set_text_scale(state.config.accessibility.text_scale);
+    }
+    let main_weak_theme = main_window.as_weak();
+    theme::watch_system(move |dark| {
+        let _ = main_weak_theme
+            .upgrade_in_event_loop(move |app| app.global::<AppPalette>().set_system_dark(dark));
+    });
+
+    // Sync initial settings UI
+    sync_settings_ui(main_window, &state.borrow());
+    main_window.set_time_display(format_duration_mm_ss(state.borrow().work_duration));
+
     // Toggle timer callback
     let state_toggle = state.clone();
     let main_weak_toggle = main_window.as_weak();
+    let ticker_toggle = ticker.clone();
     main_window.on_toggle_timer(move || {
         let mut state = state_toggle.borrow_mut();
-        state.is_paused = !state.is_paused;
         if let Some(app) = main_weak_toggle.upgrade() {
-            app.set_is_paused(state.is_paused);
+            let paused = !state.is_paused;
+            set_paused(&mut state, Some(&app), paused, None);
+            ticker_toggle.kick();
         }
     });
 
     // Secondary action (reset/skip) callback
     let state_secondary = state.clone();
     let main_weak_secondary = main_window.as_weak();
+    let ticker_secondary = ticker.clone();
     main_window.on_secondary_action(move || {
-        let mut state = state_secondary.borrow_mut();
-        state.start_time = Instant::now();
         if let Some(app) = main_weak_secondary.upgrade() {
-            match state.current_mode {
-                Mode::Work => {
-                    app.set_time_display(format_duration_mm_ss(state.work_duration));
-                    app.set_progress(1.0);
-                }
-                Mode::Rest => {
-                    state.current_mode = Mode::Work;
-                    hide_rest_overlay(&mut state);
-                    let _ = app.window().show();
-                    state.main_window_visible = true;
-                    app.set_status_text("Focus Time".into());
-                    app.set_time_display(format_duration_mm_ss(state.work_duration));
-                    app.set_progress(1.0);
-                }
-            }
+            skip_or_reset(&mut state_secondary.borrow_mut(), Some(&app));
+            ticker_secondary.kick();
         }
     });
 
-    // Apply work minutes callback
-    let state_apply_minutes = state.clone();
-    let main_weak_apply_minutes = main_window.as_weak();
-    main_window.on_apply_work_minutes(move |minutes| {
-        let minutes = minutes.clamp(1, 180);
-        let mut state = state_apply_minutes.borrow_mut();
-        state.work_duration = Duration::from_secs(minutes as u64 * 60);
+    // Work duration stepper: adaptive step sizes
+    let state_step_work = state.clone();
+    main_window.on_step_work_duration(move |text, up| {
+        let current = parse_duration(&text).unwrap_or(state_step_work.borrow().work_duration);
+        format_duration(step_work_duration(current, up)).into()
+    });
+
+    // Apply work duration callback
+    let state_apply_work = state.clone();
+    let main_weak_apply_work = main_window.as_weak();
+    let ticker_apply_work = ticker.clone();
+    main_window.on_apply_work_duration(move |text| {
+        let mut state = state_apply_work.borrow_mut();
+        // 无法解析的输入保持原设置不变
+        let Some(duration) = parse_duration(&text) else {
+            if let Some(app) = main_weak_apply_work.upgrade() {
+                app.set_work_duration(format_duration(state.work_duration).into());
+            }
+            return;
+        };
+        let duration = duration.clamp(MIN_WORK_DURATION, MAX_WORK_DURATION);
+        // 在设置面板里改过之后不再使用命令行指定的时长
+        state.work_override = None;
+        state.work_duration = duration;
+        state.config.active_mut().work = duration;
         if state.current_mode == Mode::Work {
             state.start_time = Instant::now();
             state.last_tick = Instant::now();
         }
 
-        if let Some(app) = main_weak_apply_minutes.upgrade() {
-            app.set_work_minutes(minutes);
+        if let Some(app) = main_weak_apply_work.upgrade() {
+            app.set_work_duration(format_duration(duration).into());
             if state.current_mode == Mode::Work {
                 app.set_status_text("Focus Time".into());
                 app.set_time_display(format_duration_mm_ss(state.work_duration));
                 app.set_progress(1.0);
             }
         }
+        ticker_apply_work.kick();
     });
 
     // Apply rest seconds callback
     let state_apply_rest = state.clone();
     let main_weak_apply_rest = main_window.as_weak();
+    let ticker_apply_rest = ticker.clone();
     main_window.on_apply_rest_seconds(move |seconds| {
         let seconds = seconds.clamp(5, 300);
         let mut state = state_apply_rest.borrow_mut();
+        state.rest_override = None;
         state.rest_duration = Duration::from_secs(seconds as u64);
+        state.config.active_mut().rest_seconds = seconds as u32;
 
         if let Some(app) = main_weak_apply_rest.upgrade() {
             app.set_rest_seconds(seconds);
         }
+        ticker_apply_rest.kick();
     });
 
     // Apply water interval callback
@@ -982,6 +2121,7 @@ fn main() -> Result<(), slint::PlatformError> {
         let interval = interval.clamp(1, 20);
         let mut state = state_apply_water.borrow_mut();
         state.water_interval = interval as u32;
+        state.config.active_mut().water_interval = interval as u32;
 
         if let Some(app) = main_weak_apply_water.upgrade() {
             app.set_water_interval(interval);
@@ -995,12 +2135,133 @@ fn main() -> Result<(), slint::PlatformError> {
         let interval = interval.clamp(1, 20);
         let mut state = state_apply_walk.borrow_mut();
         state.walk_interval = interval as u32;
+        state.config.active_mut().walk_interval = interval as u32;
 
         if let Some(app) = main_weak_apply_walk.upgrade() {
             app.set_walk_interval(interval);
         }
     });
 
+    // Apply overlay style callback
+    let state_apply_style = state.clone();
+    main_window.on_apply_overlay_style(move |style| {
+        state_apply_style.borrow_mut().config.overlay.style =
+            OverlayStyle::from_index(style.max(0) as usize);
+    });
+
+    let state_compact_opacity = state.clone();
+    main_window.on_apply_compact_opacity(move |opacity| {
+        state_compact_opacity.borrow_mut().config.compact.opacity = opacity.clamp(0.2, 1.0);
+    });
+
+    // Launch at login is stored by the system (autostart entry / Run key), not in config
+    let main_weak_login = main_window.as_weak();
+    main_window.on_apply_launch_at_login(move |enabled| {
+        if autostart::is_enabled() == enabled {
+            return;
+        }
+        if let Err(e) = autostart::set_enabled(enabled) {
+            eprintln!("Failed to update launch at login: {}", e);
+            if let Some(app) = main_weak_login.upgrade() {
+                app.set_launch_at_login(autostart::is_enabled());
+            }
+        }
+    });
+
+    let state_start_minimized = state.clone();
+    main_window.on_apply_start_minimized(move |minimized| {
+        state_start_minimized
+            .borrow_mut()
+            .config
+            .window
+            .start_minimized = minimized;
+    });
+
+    // Theme callbacks: the header button and the settings panel
+    let state_toggle_theme = state.clone();
+    let main_weak_toggle_theme = main_window.as_weak();
+    main_window.on_toggle_theme(move || {
+        if let Some(app) = main_weak_toggle_theme.upgrade() {
+            toggle_theme(&mut state_toggle_theme.borrow_mut(), &app);
+        }
+    });
+
+    let state_apply_theme = state.clone();
+    let main_weak_apply_theme = main_window.as_weak();
+    main_window.on_apply_theme_mode(move |mode| {
+        if let Some(app) = main_weak_apply_theme.upgrade() {
+            set_theme_mode(
+                &mut state_apply_theme.borrow_mut(),
+                &app,
+                ThemeMode::from_index(mode.max(0) as usize),
+            );
+        }
+    });
+
+    let state_accessibility = state.clone();
+    let main_weak_accessibility = main_window.as_weak();
+    main_window.on_apply_accessibility(move |high_contrast, text_scale| {
+        if let Some(app) = main_weak_accessibility.upgrade() {
+            set_accessibility(
+                &mut state_accessibility.borrow_mut(),
+                &app,
+                high_contrast,
+                text_scale,
+            );
+        }
+    });
+
+    // Global hotkey recorder: turns the pressed keys into the config notation
+    main_window.on_format_hotkey(|text, ctrl, alt, shift, meta| {
+        let chord = KeyChord::from_event(&text, ctrl, alt, shift, meta);
+        if chord.is_modifier() {
+            return SharedString::new();
+        }
+        chord.to_string().into()
+    });
+
+    // Apply global hotkeys callback
+    let state_apply_hotkeys = state.clone();
+    main_window.on_apply_hotkeys(move |pause, rest_now, skip_next, show_window| {
+        let mut state = state_apply_hotkeys.borrow_mut();
+        let hotkeys = HotkeySettings {
+            pause: pause.into(),
+            rest_now: rest_now.into(),
+            skip_next: skip_next.into(),
+            show_window: show_window.into(),
+        };
+        // 没有变化时不重新注册，Wayland 下每次注册都可能请用户确认
+        if hotkeys == state.config.hotkeys {
+            return;
+        }
+        state.config.hotkeys = hotkeys;
+        // 先注销旧的，相同的组合才能再次注册
+        state.hotkeys = Hotkeys::default();
+        state.hotkeys = Hotkeys::register(&state.config.hotkeys);
+    });
+
+    // Persist settings after the panel applies them
+    let state_save = state.clone();
+    main_window.on_save_settings(move || {
+        let _ = state_save.borrow().config.save();
+    });
+
+    // Profile selection callback
+    let state_profile = state.clone();
+    let main_weak_profile = main_window.as_weak();
+    let ticker_profile = ticker.clone();
+    main_window.on_select_profile(move |index| {
+        if let Some(app) = main_weak_profile.upgrade() {
+            switch_profile(
+                &mut state_profile.borrow_mut(),
+                Some(&app),
+                index.max(0) as usize,
+                true,
+            );
+            ticker_profile.kick();
+        }
+    });
+
     // Window drag callbacks
     let state_drag_start = state.clone();
     let main_weak_drag_start = main_window.as_weak();
@@ -1048,10 +2309,27 @@ fn main() -> Result<(), slint::PlatformError> {
     });
 
     let state_drag_end = state.clone();
+    let main_weak_drag_end = main_window.as_weak();
     main_window.on_end_window_drag(move || {
         let mut state = state_drag_end.borrow_mut();
         state.drag_anchor_window_pos = None;
         state.drag_anchor_pointer_screen_pos = None;
+        if let Some(app) = main_weak_drag_end.upgrade() {
+            remember_main_window(&mut state, &app);
+            if state.config.compact.enabled && state.config.compact.snap_distance > 0 {
+                snap_compact_window(&mut state, &app);
+            }
+        }
+    });
+
+    // Compact mode: the header button collapses, the pill's button or a double click expands
+    let state_compact = state.clone();
+    let main_weak_compact = main_window.as_weak();
+    main_window.on_toggle_compact(move || {
+        if let Some(app) = main_weak_compact.upgrade() {
+            let compact = !state_compact.borrow().config.compact.enabled;
+            set_compact(&state_compact, &app, compact);
+        }
     });
 
     // Minimize to tray callback (X button)
@@ -1059,21 +2337,107 @@ fn main() -> Result<(), slint::PlatformError> {
     let state_min = state.clone();
     main_window.on_minimize_to_tray(move || {
         if let Some(app) = main_weak_min.upgrade() {
-            let _ = app.window().hide();
-            state_min.borrow_mut().main_window_visible = false;
+            hide_main_window(&mut state_min.borrow_mut(), &app);
         }
     });
 
+    // Keyboard shortcuts configured in config.toml
+    let state_shortcut = state.clone();
+    let main_weak_shortcut = main_window.as_weak();
+    let ticker_shortcut = ticker.clone();
+    main_window.on_shortcut(move |text, ctrl, alt, shift, meta| {
+        let Some(app) = main_weak_shortcut.upgrade() else {
+            return false;
+        };
+        let mut state = state_shortcut.borrow_mut();
+        match state
+            .keymap
+            .action(&KeyChord::from_event(&text, ctrl, alt, shift, meta))
+        {
+            Some(KeyAction::TogglePause) => {
+                let paused = !state.is_paused;
+                set_paused(&mut state, Some(&app), paused, None);
+            }
+            Some(KeyAction::Skip) => skip_or_reset(&mut state, Some(&app)),
+            Some(KeyAction::OpenSettings) => app.invoke_show_settings(),
+            Some(KeyAction::HideToTray) => hide_main_window(&mut state, &app),
+            Some(KeyAction::Postpone) | None => return false,
+        }
+        ticker_shortcut.kick();
+        true
+    });
+
     main_window.on_open_settings(move || {
         // Settings panel is handled in Slint UI
     });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use i_slint_backend_testing::{AccessibleRole, ElementHandle};
+    use std::cell::Cell;
+
+    /// 按读屏标签与角色找到唯一的元素；`Text` 的文字本身也是标签，所以要一起比较角色
+    fn find(app: &MainWindow, label: &str, role: AccessibleRole) -> ElementHandle {
+        let mut found = ElementHandle::find_by_accessible_label(app, label)
+            .filter(|element| element.accessible_role() == Some(role));
+        let element = found
+            .next()
+            .unwrap_or_else(|| panic!("no {:?} labelled {:?}", role, label));
+        assert!(
+            found.next().is_none(),
+            "more than one {:?} labelled {:?}",
+            role,
+            label
+        );
+        element
+    }
 
-    // Show main window and run event loop until quit
-    main_window.show()?;
+    #[test]
+    fn main_controls_have_roles_and_labels() {
+        i_slint_backend_testing::init_no_event_loop();
+        let app = MainWindow::new().unwrap();
+
+        for label in [
+            "暂停计时",
+            "重新计时",
+            "设置",
+            "切换到深色主题",
+            "切换到迷你模式",
+            "隐藏到托盘",
+        ] {
+            find(&app, label, AccessibleRole::Button);
+        }
 
-    // Use run_event_loop_until_quit which doesn't exit when all windows are hidden.
-    // The timers we created above will keep the event loop alive.
-    slint::run_event_loop_until_quit()?;
+        // 标签随状态变化
+        app.set_is_paused(true);
+        find(&app, "继续计时", AccessibleRole::Button);
+        app.global::<AppPalette>().set_theme_mode(2);
+        find(&app, "切换到浅色主题", AccessibleRole::Button);
+
+        // 倒计时读作进度条：标签是状态，值是剩余时间
+        app.set_status_text("专注中".into());
+        app.set_time_display("19:59".into());
+        let progress = find(&app, "专注中", AccessibleRole::ProgressIndicator);
+        assert_eq!(progress.accessible_value().as_deref(), Some("19:59"));
+
+        // 迷你模式只剩小胶囊和展开按钮
+        app.set_compact(true);
+        find(&app, "展开主窗口", AccessibleRole::Button);
+        let pill = find(&app, "专注中", AccessibleRole::ProgressIndicator);
+        assert_eq!(pill.accessible_value().as_deref(), Some("19:59"));
+    }
 
-    Ok(())
+    #[test]
+    fn default_action_clicks_the_button() {
+        i_slint_backend_testing::init_no_event_loop();
+        let app = MainWindow::new().unwrap();
+        let toggled = Rc::new(Cell::new(0));
+        let counter = toggled.clone();
+        app.on_toggle_timer(move || counter.set(counter.get() + 1));
+
+        find(&app, "暂停计时", AccessibleRole::Button).invoke_accessible_default_action();
+        assert_eq!(toggled.get(), 1);
+    }
 }
@@ -0,0 +1,52 @@
+//! 桌面通知：无窗口模式下用来提醒休息开始与结束
+//!
+//! Linux 通过 D-Bus 调用 `org.freedesktop.Notifications` 的 `Notify`；其他系统暂不支持，
+//! 只有标准输出中的那一行。
+
+/// 在后台线程发出一条通知，失败时只记录日志
+pub fn send(summary: &str, body: &str) {
+    #[cfg(target_os = "linux")]
+    {
+        let (summary, body) = (summary.to_string(), body.to_string());
+        std::thread::spawn(move || {
+            if let Err(e) = linux::notify(&summary, &body) {
+                eprintln!("Failed to send notification: {}", e);
+            }
+        });
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = (summary, body);
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::collections::HashMap;
+    use zbus::blocking::Connection;
+    use zbus::zvariant::Value;
+
+    /// 由通知服务决定显示多久
+    const DEFAULT_TIMEOUT: i32 = -1;
+
+    pub fn notify(summary: &str, body: &str) -> zbus::Result<()> {
+        let conn = Connection::session()?;
+        let actions: Vec<&str> = Vec::new();
+        let hints: HashMap<&str, Value> = HashMap::new();
+        conn.call_method(
+            Some("org.freedesktop.Notifications"),
+            "/org/freedesktop/Notifications",
+            Some("org.freedesktop.Notifications"),
+            "Notify",
+            &(
+                "瞎了么",
+                0u32,
+                "are_you_blind",
+                summary,
+                body,
+                actions,
+                hints,
+                DEFAULT_TIMEOUT,
+            ),
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+//! 遮罩窗口的摆放规划：根据当前显示器与已有遮罩，决定新建、关闭和重新摆放哪些窗口
+//!
+//! 这里只做计算，不接触窗口，方便配合 `platform::MockDisplays` 测试。
+
+use crate::platform::MonitorRect;
+
+/// 去掉配置为不覆盖的输出（按名字匹配，不区分大小写）
+///
+/// 如果所有显示器都被排除，则忽略排除设置，避免休息时没有任何遮罩。
+pub fn filter_outputs(monitors: &[MonitorRect], skip: &[String]) -> Vec<MonitorRect> {
+    let kept: Vec<MonitorRect> = monitors
+        .iter()
+        .filter(|monitor| {
+            !skip
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(&monitor.name))
+        })
+        .cloned()
+        .collect();
+    if kept.is_empty() {
+        monitors.to_vec()
+    } else {
+        kept
+    }
+}
+
+/// 显示完整内容的显示器：系统主显示器，主显示器被排除或不存在时取第一个
+pub fn detail_monitor(monitors: &[MonitorRect]) -> Option<&MonitorRect> {
+    monitors
+        .iter()
+        .find(|monitor| monitor.primary)
+        .or_else(|| monitors.first())
+}
+
+/// 一次调整需要做的事情
+#[derive(Debug, Default, PartialEq)]
+pub struct OverlayPlan {
+    /// 需要关闭的已有遮罩（下标，从小到大）
+    pub remove: Vec<usize>,
+    /// 需要按新区域重新摆放的已有遮罩
+    pub refit: Vec<(usize, MonitorRect)>,
+    /// 需要新建的遮罩；`None` 表示找不到任何显示器时的兜底全屏窗口
+    pub create: Vec<Option<MonitorRect>>,
+}
+
+impl OverlayPlan {
+    pub fn is_empty(&self) -> bool {
+        self.remove.is_empty() && self.refit.is_empty() && self.create.is_empty()
+    }
+}
+
+/// `existing` 为每个已有遮罩覆盖的显示器（`None` 为兜底全屏窗口），`monitors` 为当前显示器
+///
+/// 每个显示器保持恰好一个遮罩；没有任何显示器时保留或新建一个兜底窗口。
+pub fn plan_overlays(existing: &[Option<MonitorRect>], monitors: &[MonitorRect]) -> OverlayPlan {
+    let mut plan = OverlayPlan::default();
+    let mut covered = vec![false; monitors.len()];
+
+    for (index, overlay) in existing.iter().enumerate() {
+        let Some(old) = overlay else {
+            // 兜底窗口只在没有可用显示器时保留
+            if !monitors.is_empty() {
+                plan.remove.push(index);
+            }
+            continue;
+        };
+
+        let found = monitors
+            .iter()
+            .enumerate()
+            .find(|(i, monitor)| !covered[*i] && monitor.same_output(old));
+        match found {
+            Some((i, monitor)) => {
+                covered[i] = true;
+                if monitor != old {
+                    plan.refit.push((index, monitor.clone()));
+                }
+            }
+            None => plan.remove.push(index),
+        }
+    }
+
+    plan.create = monitors
+        .iter()
+        .zip(&covered)
+        .filter(|(_, covered)| !**covered)
+        .map(|(monitor, _)| Some(monitor.clone()))
+        .collect();
+
+    let kept = existing.len() - plan.remove.len();
+    if kept == 0 && plan.create.is_empty() {
+        plan.create.push(None);
+    }
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::{DisplayProvider, MockDisplays};
+
+    fn monitors(spec: &str) -> Vec<MonitorRect> {
+        MockDisplays::parse(spec).unwrap().monitors()
+    }
+
+    #[test]
+    fn creates_one_overlay_per_monitor() {
+        let current = monitors("DP-1:1920x1080+0+0,HDMI-1:2560x1440+1920+0");
+        let plan = plan_overlays(&[], &current);
+        assert!(plan.remove.is_empty() && plan.refit.is_empty());
+        assert_eq!(
+            plan.create,
+            [Some(current[0].clone()), Some(current[1].clone())]
+        );
+    }
+
+    #[test]
+    fn unchanged_monitors_need_nothing() {
+        let current = monitors("DP-1:1920x1080+0+0,HDMI-1:2560x1440+1920+0");
+        let existing: Vec<_> = current.iter().cloned().map(Some).collect();
+        assert!(plan_overlays(&existing, &current).is_empty());
+    }
+
+    #[test]
+    fn refits_moved_monitor_and_removes_duplicates() {
+        let old = monitors("DP-1:1920x1080+0+0")[0].clone();
+        let current = monitors("DP-1:2560x1440+0+0");
+        // 同一个输出上意外有两个遮罩时只保留第一个
+        let plan = plan_overlays(&[Some(old.clone()), Some(old)], &current);
+        assert_eq!(plan.refit, [(0, current[0].clone())]);
+        assert_eq!(plan.remove, [1]);
+        assert!(plan.create.is_empty());
+    }
+
+    #[test]
+    fn unnamed_outputs_match_by_position() {
+        let mut old = monitors(":1920x1080+0+0")[0].clone();
+        old.scale_factor = 2.0;
+        let current = monitors(":1920x1080+0+0,:1920x1080+1920+0");
+        let plan = plan_overlays(&[Some(old)], &current);
+        assert_eq!(plan.refit, [(0, current[0].clone())]);
+        assert_eq!(plan.create, [Some(current[1].clone())]);
+    }
+
+    #[test]
+    fn fallback_overlay_only_without_monitors() {
+        assert_eq!(plan_overlays(&[], &[]).create, [None]);
+        assert!(plan_overlays(&[None], &[]).is_empty());
+
+        let current = monitors("DP-1:1920x1080+0+0");
+        let plan = plan_overlays(&[None], &current);
+        assert_eq!(plan.remove, [0]);
+        assert_eq!(plan.create, [Some(current[0].clone())]);
+    }
+
+    #[test]
+    fn filter_outputs_ignores_case_and_never_empties() {
+        let current = monitors("DP-1:1920x1080+0+0,HDMI-1:2560x1440+1920+0");
+        let kept = filter_outputs(&current, &["dp-1".to_string()]);
+        assert_eq!(kept, [current[1].clone()]);
+        assert_eq!(
+            filter_outputs(&current[..1], &["DP-1".to_string()]),
+            current[..1]
+        );
+    }
+
+    #[test]
+    fn detail_monitor_prefers_primary() {
+        let mut current = monitors("DP-1:1920x1080+0+0,HDMI-1:2560x1440+1920+0");
+        assert_eq!(detail_monitor(&current).unwrap().name, "DP-1");
+        current[0].primary = false;
+        current[1].primary = true;
+        assert_eq!(detail_monitor(&current).unwrap().name, "HDMI-1");
+        // 主显示器被排除后取第一个
+        assert_eq!(detail_monitor(&current[..1]).unwrap().name, "DP-1");
+        assert!(detail_monitor(&[]).is_none());
+    }
+}
@@ -0,0 +1,423 @@
+//! Linux：Wayland 下读取 wl_output 与 xdg_output，X11 下读取 XRandR；空闲时间来自 X11
+//! MIT-SCREEN-SAVER，鼠标位置来自 X11 QueryPointer
+//!
+//! 休息期间每秒都要枚举显示器、查询空闲时间，所以 X11 与 Wayland 各保持一个连接（只在 UI
+//! 线程上使用），连接断开时丢弃，下次查询再重新连接。
+
+use super::{MonitorRect, WorkArea};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use wayland_client::protocol::{wl_output, wl_registry};
+use wayland_client::{Connection, Dispatch, EventQueue, Proxy, QueueHandle, WEnum};
+use wayland_protocols::xdg::xdg_output::zv1::client::{
+    zxdg_output_manager_v1::ZxdgOutputManagerV1, zxdg_output_v1,
+};
+use x11rb::cookie::Cookie;
+use x11rb::errors::{ConnectionError, ReplyError};
+use x11rb::rust_connection::RustConnection;
+use x11rb::x11_utils::TryParse;
+
+thread_local! {
+    static X11_CONNECTION: RefCell<Option<Rc<X11>>> = const { RefCell::new(None) };
+    static WAYLAND_OUTPUTS: RefCell<Option<WaylandOutputs>> = const { RefCell::new(None) };
+}
+
+/// 与 winit 的后端选择一致：设置了 `WAYLAND_DISPLAY` 时优先 Wayland
+pub fn monitor_rects() -> Vec<MonitorRect> {
+    let wayland = std::env::var_os("WAYLAND_DISPLAY")
+        .is_some()
+        .then(wayland_monitor_rects)
+        .flatten();
+    wayland.or_else(x11_monitor_rects).unwrap_or_default()
+}
+
+/// Wayland 没有通用的空闲时间查询（XWayland 只能看到 X 客户端的输入），只支持 X11
+pub fn idle_time() -> Option<Duration> {
+    use x11rb::protocol::screensaver::ConnectionExt as _;
+
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        return None;
+    }
+    let x11 = X11::shared()?;
+    let info = x11.reply(x11.conn.screensaver_query_info(x11.root))?;
+    Some(Duration::from_millis(info.ms_since_user_input as u64))
+}
+
+/// 共用的 X11 连接与默认屏幕的根窗口
+struct X11 {
+    conn: RustConnection,
+    root: x11rb::protocol::xproto::Window,
+}
+
+impl X11 {
+    /// 取得共用的连接，还没有连接或上次连接已断开时重新连接
+    fn shared() -> Option<Rc<Self>> {
+        use x11rb::connection::Connection;
+
+        X11_CONNECTION.with_borrow_mut(|shared| {
+            if shared.is_none() {
+                let (conn, screen_num) = x11rb::connect(None).ok()?;
+                let root = conn.setup().roots.get(screen_num)?.root;
+                *shared = Some(Rc::new(Self { conn, root }));
+            }
+            shared.clone()
+        })
+    }
+
+    /// 等待请求的回复；连接断开（例如 X 服务器重启）时丢弃共用连接
+    fn reply<R: TryParse>(
+        &self,
+        cookie: Result<Cookie<'_, RustConnection, R>, ConnectionError>,
+    ) -> Option<R> {
+        match cookie.map_err(ReplyError::from).and_then(Cookie::reply) {
+            Ok(reply) => Some(reply),
+            // 服务器不支持某个扩展时连接仍然可用
+            Err(ReplyError::ConnectionError(ConnectionError::UnsupportedExtension)) => None,
+            Err(ReplyError::ConnectionError(_)) => {
+                X11_CONNECTION.set(None);
+                None
+            }
+            Err(ReplyError::X11Error(_)) => None,
+        }
+    }
+}
+
+/// 复用共用的 X11 连接反复查询鼠标位置
+pub struct X11Cursor {
+    x11: Rc<X11>,
+}
+
+impl X11Cursor {
+    /// Wayland 会话中 XWayland 只能看到 X 客户端上方的鼠标，直接放弃
+    pub fn connect() -> Option<Self> {
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            return None;
+        }
+        X11::shared().map(|x11| Self { x11 })
+    }
+
+    pub fn position(&self) -> Option<(i32, i32)> {
+        use x11rb::protocol::xproto::ConnectionExt as _;
+
+        let x11 = &self.x11;
+        let reply = x11.reply(x11.conn.query_pointer(x11.root))?;
+        Some((reply.root_x as i32, reply.root_y as i32))
+    }
+}
+
+/// 通过 RandR 1.5 的 GetMonitors 枚举；缩放比例取自 `Xft.dpi`
+///
+/// 在 Xvfb 中可以用 `xrandr --setmonitor` 把一块大屏幕切成多个虚拟显示器来测试。
+fn x11_monitor_rects() -> Option<Vec<MonitorRect>> {
+    use x11rb::protocol::randr::ConnectionExt as _;
+    use x11rb::protocol::xproto::{AtomEnum, ConnectionExt as _};
+
+    let x11 = X11::shared()?;
+    let (conn, root) = (&x11.conn, x11.root);
+
+    // 与 winit 相同：Xft.dpi / 96，未设置时按 1.0
+    let scale_factor = x11
+        .reply(conn.get_property(
+            false,
+            root,
+            AtomEnum::RESOURCE_MANAGER,
+            AtomEnum::STRING,
+            0,
+            u32::MAX / 4,
+        ))
+        .and_then(|reply| {
+            String::from_utf8_lossy(&reply.value)
+                .lines()
+                .find_map(|line| line.strip_prefix("Xft.dpi:")?.trim().parse::<f32>().ok())
+        })
+        .map_or(1.0, |dpi| dpi / 96.0);
+
+    // _NET_WORKAREA 描述整个桌面的可用区域，与各显示器求交得到各自的可用区域
+    let desktop_work_area = x11
+        .reply(conn.intern_atom(false, b"_NET_WORKAREA"))
+        .and_then(|atom| {
+            x11.reply(conn.get_property(false, root, atom.atom, AtomEnum::CARDINAL, 0, 4))
+        })
+        .and_then(|reply| {
+            let values: Vec<u32> = reply.value32()?.collect();
+            (values.len() >= 4).then(|| WorkArea {
+                x: values[0] as i32,
+                y: values[1] as i32,
+                width: values[2],
+                height: values[3],
+            })
+        });
+
+    let reply = x11.reply(conn.randr_get_monitors(root, true))?;
+    let monitors = reply
+        .monitors
+        .iter()
+        .filter(|m| m.width > 0 && m.height > 0)
+        .map(|m| {
+            let name = x11
+                .reply(conn.get_atom_name(m.name))
+                .map(|reply| String::from_utf8_lossy(&reply.name).into_owned())
+                .unwrap_or_default();
+            let bounds = WorkArea {
+                x: m.x as i32,
+                y: m.y as i32,
+                width: m.width as u32,
+                height: m.height as u32,
+            };
+            MonitorRect {
+                name,
+                x: bounds.x,
+                y: bounds.y,
+                width: bounds.width,
+                height: bounds.height,
+                scale_factor,
+                primary: m.primary,
+                work_area: desktop_work_area
+                    .and_then(|area| area.intersect(&bounds))
+                    .unwrap_or(bounds),
+            }
+        })
+        .collect::<Vec<_>>();
+    (!monitors.is_empty()).then_some(monitors)
+}
+
+/// 读取各输出在合成器逻辑坐标中的区域；连接断开时丢弃，下次重新连接
+fn wayland_monitor_rects() -> Option<Vec<MonitorRect>> {
+    WAYLAND_OUTPUTS.with_borrow_mut(|shared| {
+        if shared.is_none() {
+            *shared = WaylandOutputs::connect();
+        }
+        let Some(monitors) = shared.as_mut()?.monitors() else {
+            *shared = None;
+            return None;
+        };
+        (!monitors.is_empty()).then_some(monitors)
+    })
+}
+
+struct WaylandOutput {
+    /// wl_registry 中的全局对象名，输出被移除时按它查找
+    global: u32,
+    wl_output: wl_output::WlOutput,
+    xdg_output: Option<zxdg_output_v1::ZxdgOutputV1>,
+    name: String,
+    /// wl_output 的位置；没有 xdg_output 时使用
+    position: (i32, i32),
+    /// 当前模式的物理像素尺寸，未旋转
+    mode: (i32, i32),
+    scale: i32,
+    /// xdg_output 给出的逻辑位置与尺寸，已计入缩放与旋转
+    logical_position: Option<(i32, i32)>,
+    logical_size: Option<(i32, i32)>,
+}
+
+impl WaylandOutput {
+    /// 逻辑坐标中的区域；属性还没收到时返回 None。Wayland 没有可用区域的概念
+    fn rect(&self) -> Option<MonitorRect> {
+        let scale = self.scale.max(1);
+        let (x, y) = self.logical_position.unwrap_or(self.position);
+        let (width, height) = self
+            .logical_size
+            .unwrap_or((self.mode.0 / scale, self.mode.1 / scale));
+        if width <= 0 || height <= 0 {
+            return None;
+        }
+        // 分数缩放时 wl_output 只给出向上取整的整数，按物理与逻辑尺寸之比计算
+        let physical = self.mode.0.max(self.mode.1);
+        let scale_factor = if physical > 0 {
+            physical as f32 / width.max(height) as f32
+        } else {
+            scale as f32
+        };
+        Some(
+            MonitorRect {
+                name: self.name.clone(),
+                x,
+                y,
+                width: width as u32,
+                height: height as u32,
+                scale_factor,
+                primary: false,
+                work_area: WorkArea::default(),
+            }
+            .with_full_work_area(),
+        )
+    }
+}
+
+/// 绑定的输出；新输出在下一次往返后才有属性
+#[derive(Default)]
+struct Outputs {
+    list: Vec<WaylandOutput>,
+    xdg_manager: Option<ZxdgOutputManagerV1>,
+    bound_new: bool,
+}
+
+impl Outputs {
+    fn bind_xdg_output(&mut self, index: usize, qh: &QueueHandle<Self>) {
+        let (Some(manager), Some(output)) = (&self.xdg_manager, self.list.get_mut(index)) else {
+            return;
+        };
+        if output.xdg_output.is_none() {
+            output.xdg_output = Some(manager.get_xdg_output(&output.wl_output, qh, output.global));
+        }
+    }
+}
+
+impl Dispatch<wl_registry::WlRegistry, ()> for Outputs {
+    fn event(
+        state: &mut Self,
+        registry: &wl_registry::WlRegistry,
+        event: wl_registry::Event,
+        _: &(),
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_registry::Event::Global {
+                name,
+                interface,
+                version,
+            } if interface == "wl_output" => {
+                let wl_output = registry.bind(name, version.min(4), qh, name);
+                state.list.push(WaylandOutput {
+                    global: name,
+                    wl_output,
+                    xdg_output: None,
+                    name: String::new(),
+                    position: (0, 0),
+                    mode: (0, 0),
+                    scale: 1,
+                    logical_position: None,
+                    logical_size: None,
+                });
+                state.bound_new = true;
+                state.bind_xdg_output(state.list.len() - 1, qh);
+            }
+            wl_registry::Event::Global {
+                name,
+                interface,
+                version,
+            } if interface == "zxdg_output_manager_v1" => {
+                state.xdg_manager = Some(registry.bind(name, version.min(3), qh, ()));
+                state.bound_new = true;
+                // 管理器可能在输出之后才公布
+                for index in 0..state.list.len() {
+                    state.bind_xdg_output(index, qh);
+                }
+            }
+            wl_registry::Event::GlobalRemove { name } => {
+                if let Some(index) = state.list.iter().position(|o| o.global == name) {
+                    let output = state.list.remove(index);
+                    if let Some(xdg_output) = output.xdg_output {
+                        xdg_output.destroy();
+                    }
+                    if output.wl_output.version() >= 3 {
+                        output.wl_output.release();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<wl_output::WlOutput, u32> for Outputs {
+    fn event(
+        state: &mut Self,
+        _: &wl_output::WlOutput,
+        event: wl_output::Event,
+        global: &u32,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let Some(output) = state.list.iter_mut().find(|o| o.global == *global) else {
+            return;
+        };
+        match event {
+            wl_output::Event::Geometry { x, y, .. } => output.position = (x, y),
+            wl_output::Event::Mode {
+                flags: WEnum::Value(flags),
+                width,
+                height,
+                ..
+            } if flags.contains(wl_output::Mode::Current) => output.mode = (width, height),
+            wl_output::Event::Scale { factor } => output.scale = factor,
+            wl_output::Event::Name { name } => output.name = name,
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<zxdg_output_v1::ZxdgOutputV1, u32> for Outputs {
+    fn event(
+        state: &mut Self,
+        _: &zxdg_output_v1::ZxdgOutputV1,
+        event: zxdg_output_v1::Event,
+        global: &u32,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let Some(output) = state.list.iter_mut().find(|o| o.global == *global) else {
+            return;
+        };
+        match event {
+            zxdg_output_v1::Event::LogicalPosition { x, y } => {
+                output.logical_position = Some((x, y));
+            }
+            zxdg_output_v1::Event::LogicalSize { width, height } => {
+                output.logical_size = Some((width, height));
+            }
+            // wl_output 第 4 版之前只能从这里取得名字
+            zxdg_output_v1::Event::Name { name } if output.name.is_empty() => {
+                output.name = name;
+            }
+            _ => {}
+        }
+    }
+}
+
+wayland_client::delegate_noop!(Outputs: ignore ZxdgOutputManagerV1);
+
+/// 常驻的 Wayland 连接：输出的增减与属性变化以事件送达，每次查询只需往返一次
+struct WaylandOutputs {
+    queue: EventQueue<Outputs>,
+    outputs: Outputs,
+}
+
+impl WaylandOutputs {
+    fn connect() -> Option<Self> {
+        let conn = Connection::connect_to_env().ok()?;
+        let queue = conn.new_event_queue();
+        conn.display().get_registry(&queue.handle(), ());
+        Some(Self {
+            queue,
+            outputs: Outputs::default(),
+        })
+    }
+
+    /// 处理积压的事件后返回当前的输出；连接出错时返回 None
+    fn monitors(&mut self) -> Option<Vec<MonitorRect>> {
+        // 新绑定的输出（包括第一次连接时的全部输出）要再往返一次才能拿到属性
+        loop {
+            self.outputs.bound_new = false;
+            self.queue.roundtrip(&mut self.outputs).ok()?;
+            if !self.outputs.bound_new {
+                break;
+            }
+        }
+
+        let mut monitors = self
+            .outputs
+            .list
+            .iter()
+            .filter_map(WaylandOutput::rect)
+            .collect::<Vec<_>>();
+        // Wayland 没有主显示器的概念，取第一个输出
+        if let Some(first) = monitors.first_mut() {
+            first.primary = true;
+        }
+        Some(monitors)
+    }
+}
@@ -0,0 +1,381 @@
+//! 平台相关代码：显示器枚举、DPI 感知、热插拔监视、用户空闲时间与鼠标位置
+//!
+//! 各系统的实现在子模块中（Windows: EnumDisplayMonitors；Linux: Wayland wl_output / X11 XRandR），
+//! 对外统一为 `DisplayProvider`。休息期间显示器可能被插拔或调整缩放，`MonitorWatch`
+//! 比较前后两次枚举结果，由调用方据此创建、销毁或重新摆放遮罩窗口。
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "windows")]
+mod windows;
+
+#[cfg(target_os = "windows")]
+pub use windows::virtual_screen_rect;
+
+/// 一块显示器在虚拟桌面中的物理像素区域；Wayland 下为合成器的逻辑坐标（客户端拿不到物理坐标）
+#[derive(Clone, Debug, PartialEq)]
+pub struct MonitorRect {
+    /// 系统给出的输出名，例如 `\\.\DISPLAY1`、`DP-1`；取不到时为空
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f32,
+    /// 系统标记的主显示器；没有这一概念的平台取第一个输出
+    pub primary: bool,
+    /// 除去任务栏、面板后的可用区域；平台不提供时与整个显示器相同
+    pub work_area: WorkArea,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WorkArea {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl WorkArea {
+    pub fn intersect(&self, other: &WorkArea) -> Option<WorkArea> {
+        let left = self.x.max(other.x);
+        let top = self.y.max(other.y);
+        let right = (self.x + self.width as i32).min(other.x + other.width as i32);
+        let bottom = (self.y + self.height as i32).min(other.y + other.height as i32);
+        (right > left && bottom > top).then(|| WorkArea {
+            x: left,
+            y: top,
+            width: (right - left) as u32,
+            height: (bottom - top) as u32,
+        })
+    }
+}
+
+impl MonitorRect {
+    /// 可用区域未知时视为整个显示器
+    pub fn with_full_work_area(mut self) -> Self {
+        self.work_area = self.bounds();
+        self
+    }
+
+    /// 整个显示器区域
+    pub fn bounds(&self) -> WorkArea {
+        WorkArea {
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// 按缩放比例换算的逻辑 DPI（100% = 96）
+    pub fn dpi(&self) -> u32 {
+        (self.scale_factor * 96.0).round() as u32
+    }
+
+    /// 是否为同一个输出：优先比较名字，没有名字时比较左上角位置
+    pub fn same_output(&self, other: &MonitorRect) -> bool {
+        if !self.name.is_empty() && !other.name.is_empty() {
+            self.name == other.name
+        } else {
+            (self.x, self.y) == (other.x, other.y)
+        }
+    }
+}
+
+/// 显示器列表的来源
+pub trait DisplayProvider {
+    fn monitors(&self) -> Vec<MonitorRect>;
+}
+
+/// 当前系统的显示器；平台不支持枚举时返回空列表，由调用方退回单个全屏窗口
+pub struct SystemDisplays;
+
+impl DisplayProvider for SystemDisplays {
+    fn monitors(&self) -> Vec<MonitorRect> {
+        #[cfg(target_os = "windows")]
+        {
+            windows::monitor_rects()
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            linux::monitor_rects()
+        }
+
+        #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+        {
+            Vec::new()
+        }
+    }
+}
+
+/// 内存中的模拟显示器列表，用于测试和复现摆放问题
+///
+/// 克隆共享同一份列表，测试可以在监视过程中调用 `set` 模拟热插拔。
+#[derive(Clone, Default)]
+pub struct MockDisplays {
+    monitors: Rc<RefCell<Vec<MonitorRect>>>,
+}
+
+impl MockDisplays {
+    pub fn new(monitors: Vec<MonitorRect>) -> Self {
+        Self {
+            monitors: Rc::new(RefCell::new(monitors)),
+        }
+    }
+
+    /// 解析 `名字:宽x高+x+y@缩放` 列表，以逗号分隔，例如
+    /// `DP-1:1920x1080+0+0@1,HDMI-1:2560x1440+1920+0@1.5`
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut monitors = spec
+            .split(',')
+            .filter(|item| !item.trim().is_empty())
+            .map(parse_monitor)
+            .collect::<Option<Vec<_>>>()?;
+        if let Some(first) = monitors.first_mut() {
+            first.primary = true;
+        }
+        Some(Self::new(monitors))
+    }
+
+    pub fn set(&self, monitors: Vec<MonitorRect>) {
+        *self.monitors.borrow_mut() = monitors;
+    }
+}
+
+impl DisplayProvider for MockDisplays {
+    fn monitors(&self) -> Vec<MonitorRect> {
+        self.monitors.borrow().clone()
+    }
+}
+
+fn parse_monitor(item: &str) -> Option<MonitorRect> {
+    let (name, geometry) = item.trim().split_once(':')?;
+    let (geometry, scale) = match geometry.split_once('@') {
+        Some((geometry, scale)) => (geometry, scale.parse().ok()?),
+        None => (geometry, 1.0),
+    };
+    let (size, position) = geometry.split_once('+')?;
+    let (width, height) = size.split_once('x')?;
+    let (x, y) = position.split_once('+')?;
+
+    Some(
+        MonitorRect {
+            name: name.to_string(),
+            x: x.parse().ok()?,
+            y: y.parse().ok()?,
+            width: width.parse().ok()?,
+            height: height.parse().ok()?,
+            scale_factor: scale,
+            primary: false,
+            work_area: WorkArea::default(),
+        }
+        .with_full_work_area(),
+    )
+}
+
+/// 两次枚举之间的变化
+#[derive(Debug, Default)]
+pub struct MonitorChanges {
+    pub added: Vec<MonitorRect>,
+    pub removed: Vec<MonitorRect>,
+    /// 仍然存在但位置、分辨率或缩放改变了
+    pub updated: Vec<MonitorRect>,
+}
+
+pub struct MonitorWatch {
+    source: Box<dyn DisplayProvider>,
+    current: Vec<MonitorRect>,
+}
+
+impl MonitorWatch {
+    pub fn new(source: Box<dyn DisplayProvider>) -> Self {
+        let current = source.monitors();
+        Self { source, current }
+    }
+
+    /// 按环境变量 `ARE_YOU_BLIND_FAKE_MONITORS` 使用模拟显示器，否则使用系统显示器
+    pub fn from_env() -> Self {
+        let mock = std::env::var("ARE_YOU_BLIND_FAKE_MONITORS")
+            .ok()
+            .and_then(|spec| MockDisplays::parse(&spec));
+        match mock {
+            Some(mock) => Self::new(Box::new(mock)),
+            None => Self::new(Box::new(SystemDisplays)),
+        }
+    }
+
+    /// 上一次枚举到的显示器
+    pub fn current(&self) -> &[MonitorRect] {
+        &self.current
+    }
+
+    /// 重新枚举；有变化时返回变化并更新 `current`
+    pub fn poll(&mut self) -> Option<MonitorChanges> {
+        let monitors = self.source.monitors();
+        if monitors == self.current {
+            return None;
+        }
+
+        let mut changes = MonitorChanges::default();
+        for monitor in &monitors {
+            match self.current.iter().find(|old| old.same_output(monitor)) {
+                Some(old) if old != monitor => changes.updated.push(monitor.clone()),
+                Some(_) => {}
+                None => changes.added.push(monitor.clone()),
+            }
+        }
+        changes.removed = self
+            .current
+            .iter()
+            .filter(|old| !monitors.iter().any(|monitor| monitor.same_output(old)))
+            .cloned()
+            .collect();
+
+        self.current = monitors;
+        Some(changes)
+    }
+}
+
+/// 距离最近一次键盘或鼠标输入的时间；无法获取时（例如 Wayland）返回 None
+pub fn idle_time() -> Option<Duration> {
+    #[cfg(target_os = "windows")]
+    return windows::idle_time();
+    #[cfg(target_os = "linux")]
+    return linux::idle_time();
+    #[allow(unreachable_code)]
+    None
+}
+
+/// 查询鼠标在虚拟桌面中的位置（物理像素），用于迷你模式判断鼠标是否停在小窗上
+///
+/// X11 复用同一个连接，避免每次轮询都重新连接。Wayland 不向客户端提供全局鼠标位置。
+pub struct CursorTracker {
+    #[cfg(target_os = "linux")]
+    x11: Option<linux::X11Cursor>,
+}
+
+impl CursorTracker {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(target_os = "linux")]
+            x11: linux::X11Cursor::connect(),
+        }
+    }
+
+    /// 无法获取时返回 None
+    pub fn position(&self) -> Option<(i32, i32)> {
+        #[cfg(target_os = "windows")]
+        return windows::cursor_position();
+        #[cfg(target_os = "linux")]
+        return self.x11.as_ref()?.position();
+        #[allow(unreachable_code)]
+        None
+    }
+}
+
+impl Default for CursorTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 声明按显示器感知 DPI，否则 Windows 会返回缩放后的虚拟坐标；其他平台无需处理
+pub fn enable_dpi_awareness() {
+    #[cfg(target_os = "windows")]
+    windows::enable_dpi_awareness();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::overlay_layout::{filter_outputs, plan_overlays};
+
+    const TWO_MONITORS: &str = "DP-1:1920x1080+0+0@1,HDMI-1:2560x1440+1920+0@1.5";
+
+    #[test]
+    fn parses_mock_spec() {
+        let mock = MockDisplays::parse(TWO_MONITORS).unwrap();
+        let monitors = mock.monitors();
+        assert_eq!(monitors.len(), 2);
+        assert!(monitors[0].primary && !monitors[1].primary);
+        assert_eq!(monitors[1].name, "HDMI-1");
+        assert_eq!((monitors[1].x, monitors[1].width), (1920, 2560));
+        assert_eq!(monitors[1].scale_factor, 1.5);
+        assert_eq!(monitors[1].work_area, monitors[1].bounds());
+        assert!(MockDisplays::parse("DP-1:1920x1080").is_none());
+        assert!(MockDisplays::parse("DP-1:1920x1080+0+0@big").is_none());
+    }
+
+    #[test]
+    fn fake_monitors_env_replaces_system_displays() {
+        std::env::set_var("ARE_YOU_BLIND_FAKE_MONITORS", TWO_MONITORS);
+        let watch = MonitorWatch::from_env();
+        std::env::remove_var("ARE_YOU_BLIND_FAKE_MONITORS");
+        let names: Vec<&str> = watch.current().iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["DP-1", "HDMI-1"]);
+    }
+
+    #[test]
+    fn hot_plug_drives_overlay_plan() {
+        let mock = MockDisplays::parse(TWO_MONITORS).unwrap();
+        let mut watch = MonitorWatch::new(Box::new(mock.clone()));
+        let overlays: Vec<Option<MonitorRect>> =
+            watch.current().iter().cloned().map(Some).collect();
+        assert!(watch.poll().is_none());
+
+        // 拔掉 HDMI-1，DP-1 改为 1.25 倍缩放，接上 DP-2
+        let mut monitors = mock.monitors();
+        monitors.pop();
+        monitors[0].scale_factor = 1.25;
+        monitors.push(parse_monitor("DP-2:1280x1024+3840+0").unwrap());
+        mock.set(monitors);
+
+        let changes = watch.poll().unwrap();
+        assert_eq!(changes.removed.len(), 1);
+        assert_eq!(changes.removed[0].name, "HDMI-1");
+        assert_eq!(changes.updated.len(), 1);
+        assert_eq!(changes.updated[0].scale_factor, 1.25);
+        assert_eq!(changes.added.len(), 1);
+        assert_eq!(changes.added[0].name, "DP-2");
+
+        let plan = plan_overlays(&overlays, watch.current());
+        assert_eq!(plan.remove, [1]);
+        assert_eq!(plan.refit.len(), 1);
+        assert_eq!(plan.refit[0].0, 0);
+        assert_eq!(plan.create.len(), 1);
+        assert_eq!(plan.create[0].as_ref().unwrap().name, "DP-2");
+    }
+
+    #[test]
+    fn skipped_outputs_are_not_covered() {
+        let mock = MockDisplays::parse(TWO_MONITORS).unwrap();
+        let kept = filter_outputs(&mock.monitors(), &["hdmi-1".to_string()]);
+        let plan = plan_overlays(&[], &kept);
+        assert_eq!(plan.create, [Some(mock.monitors()[0].clone())]);
+
+        // 全部排除时忽略排除设置
+        let all = ["DP-1".to_string(), "HDMI-1".to_string()];
+        let plan = plan_overlays(&[], &filter_outputs(&mock.monitors(), &all));
+        assert_eq!(plan.create.len(), 2);
+    }
+
+    #[test]
+    fn unplugging_everything_keeps_a_fallback_overlay() {
+        let mock = MockDisplays::parse(TWO_MONITORS).unwrap();
+        let mut watch = MonitorWatch::new(Box::new(mock.clone()));
+        let overlays: Vec<Option<MonitorRect>> =
+            watch.current().iter().cloned().map(Some).collect();
+
+        mock.set(Vec::new());
+        assert_eq!(watch.poll().unwrap().removed.len(), 2);
+        let plan = plan_overlays(&overlays, watch.current());
+        assert_eq!(plan.remove, [0, 1]);
+        assert_eq!(plan.create, [None]);
+    }
+}
@@ -0,0 +1,349 @@
+//! Windows：EnumDisplayMonitors 枚举显示器，GetDpiForMonitor 读取缩放，GetLastInputInfo 读取空闲时间，
+//! GetCursorPos 读取鼠标位置
+
+#![allow(clippy::upper_case_acronyms)] // Windows API types use uppercase names
+
+use super::{MonitorRect, WorkArea};
+use std::time::Duration;
+
+pub fn enable_dpi_awareness() {
+    use std::ffi::c_void;
+
+    type HMODULE = *mut c_void;
+    type FARPROC = *mut c_void;
+    type BOOL = i32;
+    type HRESULT = i32;
+
+    const PROCESS_PER_MONITOR_DPI_AWARE: i32 = 2;
+
+    fn wide_null_terminated(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn LoadLibraryW(lp_lib_file_name: *const u16) -> HMODULE;
+        fn GetProcAddress(h_module: HMODULE, lp_proc_name: *const i8) -> FARPROC;
+    }
+
+    unsafe {
+        let user32 = LoadLibraryW(wide_null_terminated("user32.dll").as_ptr());
+        if !user32.is_null() {
+            let set_context = GetProcAddress(user32, c"SetProcessDpiAwarenessContext".as_ptr());
+            if !set_context.is_null() {
+                type SetProcessDpiAwarenessContextFn =
+                    unsafe extern "system" fn(*mut c_void) -> BOOL;
+                let set_context: SetProcessDpiAwarenessContextFn = std::mem::transmute(set_context);
+                if set_context((-4isize) as *mut c_void) != 0 {
+                    return;
+                }
+            }
+        }
+
+        let shcore = LoadLibraryW(wide_null_terminated("shcore.dll").as_ptr());
+        if !shcore.is_null() {
+            let set_awareness = GetProcAddress(shcore, c"SetProcessDpiAwareness".as_ptr());
+            if !set_awareness.is_null() {
+                type SetProcessDpiAwarenessFn = unsafe extern "system" fn(i32) -> HRESULT;
+                let set_awareness: SetProcessDpiAwarenessFn = std::mem::transmute(set_awareness);
+                if set_awareness(PROCESS_PER_MONITOR_DPI_AWARE) == 0 {
+                    return;
+                }
+            }
+        }
+
+        if !user32.is_null() {
+            let set_dpi_aware = GetProcAddress(user32, c"SetProcessDPIAware".as_ptr());
+            if !set_dpi_aware.is_null() {
+                type SetProcessDPIAwareFn = unsafe extern "system" fn() -> BOOL;
+                let set_dpi_aware: SetProcessDPIAwareFn = std::mem::transmute(set_dpi_aware);
+                let _ = set_dpi_aware();
+            }
+        }
+    }
+}
+
+/// 已声明按显示器感知 DPI，得到的是物理像素
+pub fn cursor_position() -> Option<(i32, i32)> {
+    type BOOL = i32;
+
+    #[repr(C)]
+    struct POINT {
+        x: i32,
+        y: i32,
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetCursorPos(point: *mut POINT) -> BOOL;
+    }
+
+    let mut point = POINT { x: 0, y: 0 };
+    (unsafe { GetCursorPos(&mut point) } != 0).then_some((point.x, point.y))
+}
+
+pub fn idle_time() -> Option<Duration> {
+    type BOOL = i32;
+    type DWORD = u32;
+
+    #[repr(C)]
+    struct LASTINPUTINFO {
+        cb_size: u32,
+        dw_time: DWORD,
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetLastInputInfo(plii: *mut LASTINPUTINFO) -> BOOL;
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetTickCount() -> DWORD;
+    }
+
+    let mut info = LASTINPUTINFO {
+        cb_size: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dw_time: 0,
+    };
+    unsafe {
+        if GetLastInputInfo(&mut info) == 0 {
+            return None;
+        }
+        // 两者都是开机以来的毫秒数，约 49.7 天回绕一次
+        Some(Duration::from_millis(
+            GetTickCount().wrapping_sub(info.dw_time) as u64,
+        ))
+    }
+}
+
+pub fn monitor_rects() -> Vec<MonitorRect> {
+    use std::ffi::c_void;
+    use std::mem::MaybeUninit;
+    use std::ptr;
+
+    type HMONITOR = *mut c_void;
+    type HDC = *mut c_void;
+    type LPARAM = isize;
+    type BOOL = i32;
+    type UINT = u32;
+    type HRESULT = i32;
+    const CCHDEVICENAME: usize = 32;
+    const CCHFORMNAME: usize = 32;
+    const ENUM_CURRENT_SETTINGS: u32 = 0xFFFF_FFFF;
+    const MDT_EFFECTIVE_DPI: i32 = 0;
+    const MONITORINFOF_PRIMARY: u32 = 1;
+
+    #[repr(C)]
+    struct RECT {
+        left: i32,
+        top: i32,
+        right: i32,
+        bottom: i32,
+    }
+
+    #[repr(C)]
+    #[allow(non_snake_case)]
+    struct MONITORINFOEXW {
+        cbSize: u32,
+        rcMonitor: RECT,
+        rcWork: RECT,
+        dwFlags: u32,
+        szDevice: [u16; CCHDEVICENAME],
+    }
+
+    #[repr(C)]
+    #[allow(non_snake_case)]
+    struct POINTL {
+        x: i32,
+        y: i32,
+    }
+
+    #[repr(C)]
+    #[allow(non_snake_case)]
+    struct DEVMODEW {
+        dmDeviceName: [u16; CCHDEVICENAME],
+        dmSpecVersion: u16,
+        dmDriverVersion: u16,
+        dmSize: u16,
+        dmDriverExtra: u16,
+        dmFields: u32,
+        dmPosition: POINTL,
+        dmDisplayOrientation: u32,
+        dmDisplayFixedOutput: u32,
+        dmColor: i16,
+        dmDuplex: i16,
+        dmYResolution: i16,
+        dmTTOption: i16,
+        dmCollate: i16,
+        dmFormName: [u16; CCHFORMNAME],
+        dmLogPixels: u16,
+        dmBitsPerPel: u32,
+        dmPelsWidth: u32,
+        dmPelsHeight: u32,
+        dmDisplayFlags: u32,
+        dmDisplayFrequency: u32,
+        dmICMMethod: u32,
+        dmICMIntent: u32,
+        dmMediaType: u32,
+        dmDitherType: u32,
+        dmReserved1: u32,
+        dmReserved2: u32,
+        dmPanningWidth: u32,
+        dmPanningHeight: u32,
+    }
+
+    type MonitorEnumProc =
+        Option<unsafe extern "system" fn(HMONITOR, HDC, *mut RECT, LPARAM) -> BOOL>;
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn EnumDisplayMonitors(
+            hdc: HDC,
+            lprcClip: *const RECT,
+            lpfnEnum: MonitorEnumProc,
+            dwData: LPARAM,
+        ) -> BOOL;
+        fn GetMonitorInfoW(hMonitor: HMONITOR, lpmi: *mut MONITORINFOEXW) -> BOOL;
+        fn EnumDisplaySettingsW(
+            lpszDeviceName: *const u16,
+            iModeNum: u32,
+            lpDevMode: *mut DEVMODEW,
+        ) -> BOOL;
+    }
+
+    #[link(name = "Shcore")]
+    extern "system" {
+        fn GetDpiForMonitor(
+            hmonitor: HMONITOR,
+            dpiType: i32,
+            dpiX: *mut UINT,
+            dpiY: *mut UINT,
+        ) -> HRESULT;
+    }
+
+    unsafe extern "system" fn enum_monitor_cb(
+        hmonitor: HMONITOR,
+        _hdc: HDC,
+        _rect: *mut RECT,
+        data: LPARAM,
+    ) -> BOOL {
+        let monitors: &mut Vec<MonitorRect> = &mut *(data as *mut Vec<MonitorRect>);
+
+        let mut mi = MaybeUninit::<MONITORINFOEXW>::zeroed();
+        (*mi.as_mut_ptr()).cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+        if GetMonitorInfoW(hmonitor, mi.as_mut_ptr()) != 0 {
+            let mi = mi.assume_init();
+
+            let mut rect: Option<MonitorRect> = None;
+            let name_len = mi
+                .szDevice
+                .iter()
+                .position(|&c| c == 0)
+                .unwrap_or(CCHDEVICENAME);
+            let name = String::from_utf16_lossy(&mi.szDevice[..name_len]);
+            let primary = mi.dwFlags & MONITORINFOF_PRIMARY != 0;
+            let work = &mi.rcWork;
+            let work_area = WorkArea {
+                x: work.left,
+                y: work.top,
+                width: (work.right - work.left).max(0) as u32,
+                height: (work.bottom - work.top).max(0) as u32,
+            };
+
+            let mut scale_factor = 1.0f32;
+            let mut dpi_x: UINT = 0;
+            let mut dpi_y: UINT = 0;
+            if GetDpiForMonitor(hmonitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y) == 0
+                && dpi_x > 0
+            {
+                scale_factor = dpi_x as f32 / 96.0;
+            }
+
+            let mut dm = MaybeUninit::<DEVMODEW>::zeroed();
+            (*dm.as_mut_ptr()).dmSize = std::mem::size_of::<DEVMODEW>() as u16;
+            if EnumDisplaySettingsW(mi.szDevice.as_ptr(), ENUM_CURRENT_SETTINGS, dm.as_mut_ptr())
+                != 0
+            {
+                let dm = dm.assume_init();
+                rect = Some(MonitorRect {
+                    name,
+                    x: dm.dmPosition.x,
+                    y: dm.dmPosition.y,
+                    width: dm.dmPelsWidth,
+                    height: dm.dmPelsHeight,
+                    scale_factor,
+                    primary,
+                    work_area,
+                });
+            } else {
+                let r = mi.rcMonitor;
+                let width = (r.right - r.left).max(0) as u32;
+                let height = (r.bottom - r.top).max(0) as u32;
+                if width > 0 && height > 0 {
+                    rect = Some(MonitorRect {
+                        name,
+                        x: r.left,
+                        y: r.top,
+                        width,
+                        height,
+                        scale_factor,
+                        primary,
+                        work_area,
+                    });
+                }
+            }
+
+            if let Some(rect) = rect {
+                if rect.width > 0 && rect.height > 0 {
+                    monitors.push(rect);
+                }
+            }
+        }
+
+        1
+    }
+
+    let mut monitors = Vec::new();
+    unsafe {
+        let _ = EnumDisplayMonitors(
+            ptr::null_mut(),
+            ptr::null(),
+            Some(enum_monitor_cb),
+            (&mut monitors as *mut Vec<MonitorRect>) as LPARAM,
+        );
+    }
+    monitors
+}
+
+/// 整个虚拟桌面，枚举失败时的兜底区域
+pub fn virtual_screen_rect() -> MonitorRect {
+    use std::ffi::c_int;
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetSystemMetrics(nIndex: c_int) -> c_int;
+    }
+
+    const SM_XVIRTUALSCREEN: c_int = 76;
+    const SM_YVIRTUALSCREEN: c_int = 77;
+    const SM_CXVIRTUALSCREEN: c_int = 78;
+    const SM_CYVIRTUALSCREEN: c_int = 79;
+
+    let x = unsafe { GetSystemMetrics(SM_XVIRTUALSCREEN) };
+    let y = unsafe { GetSystemMetrics(SM_YVIRTUALSCREEN) };
+    let width = unsafe { GetSystemMetrics(SM_CXVIRTUALSCREEN) }.max(1) as u32;
+    let height = unsafe { GetSystemMetrics(SM_CYVIRTUALSCREEN) }.max(1) as u32;
+
+    MonitorRect {
+        name: String::new(),
+        x,
+        y,
+        width,
+        height,
+        scale_factor: 1.0,
+        primary: true,
+        work_area: WorkArea::default(),
+    }
+    .with_full_work_area()
+}
@@ -0,0 +1,228 @@
+//! 主题：浅色、深色或跟随系统，以及配置中覆盖的配色
+//!
+//! 跟随系统时，Linux 读取 xdg-desktop-portal 的 `org.freedesktop.appearance`
+//! `color-scheme` 设置，Windows 读取注册表中的 `AppsUseLightTheme`。
+
+use crate::config::{parse_hex_color, PaletteColors, ThemeSettings};
+use crate::AppPalette;
+use slint::Color;
+
+type ColorSetter = fn(&AppPalette, Color);
+
+/// 把配置中的强调色和自定义配色写入 `AppPalette`
+pub fn apply_colors(palette: &AppPalette, settings: &ThemeSettings) {
+    if let Some(accent) = settings.accent.as_deref().and_then(to_color) {
+        palette.set_accent_color(accent);
+    }
+    apply_variant(
+        palette,
+        &settings.light,
+        [
+            |palette, color| palette.set_light_background(color),
+            |palette, color| palette.set_light_text_primary(color),
+            |palette, color| palette.set_light_text_secondary(color),
+            |palette, color| palette.set_light_border(color),
+            |palette, color| palette.set_light_surface_1(color),
+            |palette, color| palette.set_light_surface_2(color),
+            |palette, color| palette.set_light_ring_track(color),
+        ],
+    );
+    apply_variant(
+        palette,
+        &settings.dark,
+        [
+            |palette, color| palette.set_dark_background(color),
+            |palette, color| palette.set_dark_text_primary(color),
+            |palette, color| palette.set_dark_text_secondary(color),
+            |palette, color| palette.set_dark_border(color),
+            |palette, color| palette.set_dark_surface_1(color),
+            |palette, color| palette.set_dark_surface_2(color),
+            |palette, color| palette.set_dark_ring_track(color),
+        ],
+    );
+}
+
+/// `setters` 与 `PaletteColors` 的字段顺序一致
+fn apply_variant(palette: &AppPalette, colors: &PaletteColors, setters: [ColorSetter; 7]) {
+    let values = [
+        &colors.background,
+        &colors.text_primary,
+        &colors.text_secondary,
+        &colors.border,
+        &colors.surface_1,
+        &colors.surface_2,
+        &colors.ring_track,
+    ];
+    for (value, set) in values.into_iter().zip(setters) {
+        // 写错的颜色保持默认
+        if let Some(color) = value.as_deref().and_then(to_color) {
+            set(palette, color);
+        }
+    }
+}
+
+fn to_color(hex: &str) -> Option<Color> {
+    let [r, g, b] = parse_hex_color(hex)?;
+    Some(Color::from_rgb_u8(r, g, b))
+}
+
+/// 系统是否偏好深色；没有偏好或无法读取时返回 None
+pub fn system_prefers_dark() -> Option<bool> {
+    #[cfg(target_os = "linux")]
+    return linux::prefers_dark();
+    #[cfg(windows)]
+    return windows::prefers_dark();
+    #[allow(unreachable_code)]
+    None
+}
+
+/// 在后台线程监听系统浅色/深色设置的变化，变化时调用 `on_change`
+pub fn watch_system(on_change: impl Fn(bool) + Send + 'static) {
+    #[cfg(target_os = "linux")]
+    std::thread::spawn(move || linux::watch(on_change));
+    #[cfg(windows)]
+    std::thread::spawn(move || windows::watch(on_change));
+    #[cfg(not(any(target_os = "linux", windows)))]
+    let _ = on_change;
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use zbus::blocking::{Connection, Proxy};
+    use zbus::zvariant::{OwnedValue, Value};
+
+    const NAMESPACE: &str = "org.freedesktop.appearance";
+    const KEY: &str = "color-scheme";
+
+    fn settings(conn: &Connection) -> zbus::Result<Proxy<'_>> {
+        Proxy::new(
+            conn,
+            "org.freedesktop.portal.Desktop",
+            "/org/freedesktop/portal/desktop",
+            "org.freedesktop.portal.Settings",
+        )
+    }
+
+    /// `color-scheme`：0 没有偏好，1 深色，2 浅色
+    fn is_dark(value: &Value) -> Option<bool> {
+        match value {
+            // 旧版门户的 Read 会多包一层 variant
+            Value::Value(inner) => is_dark(inner),
+            Value::U32(1) => Some(true),
+            Value::U32(2) => Some(false),
+            _ => None,
+        }
+    }
+
+    pub fn prefers_dark() -> Option<bool> {
+        let conn = Connection::session().ok()?;
+        let value: OwnedValue = settings(&conn).ok()?.call("Read", &(NAMESPACE, KEY)).ok()?;
+        is_dark(&value)
+    }
+
+    pub fn watch(on_change: impl Fn(bool)) {
+        let Ok(conn) = Connection::session() else {
+            return;
+        };
+        let Ok(changes) = settings(&conn).and_then(|proxy| proxy.receive_signal("SettingChanged"))
+        else {
+            return;
+        };
+        for message in changes {
+            let Ok((namespace, key, value)) =
+                message.body().deserialize::<(String, String, OwnedValue)>()
+            else {
+                continue;
+            };
+            if namespace == NAMESPACE && key == KEY {
+                on_change(is_dark(&value).unwrap_or(false));
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    #![allow(clippy::upper_case_acronyms)]
+
+    type HKEY = isize;
+    type LSTATUS = i32;
+
+    const HKEY_CURRENT_USER: HKEY = 0x8000_0001_u32 as i32 as isize;
+    const KEY_READ: u32 = 0x20019;
+    const RRF_RT_REG_DWORD: u32 = 0x0000_0018;
+    const REG_NOTIFY_CHANGE_LAST_SET: u32 = 0x0000_0004;
+    const SUBKEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize";
+    const VALUE: &str = "AppsUseLightTheme";
+
+    #[link(name = "advapi32")]
+    extern "system" {
+        fn RegOpenKeyExW(
+            key: HKEY,
+            subkey: *const u16,
+            options: u32,
+            desired: u32,
+            result: *mut HKEY,
+        ) -> LSTATUS;
+        fn RegGetValueW(
+            key: HKEY,
+            subkey: *const u16,
+            value: *const u16,
+            flags: u32,
+            kind: *mut u32,
+            data: *mut u8,
+            size: *mut u32,
+        ) -> LSTATUS;
+        fn RegNotifyChangeKeyValue(
+            key: HKEY,
+            watch_subtree: i32,
+            filter: u32,
+            event: isize,
+            asynchronous: i32,
+        ) -> LSTATUS;
+        fn RegCloseKey(key: HKEY) -> LSTATUS;
+    }
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    pub fn prefers_dark() -> Option<bool> {
+        let (subkey, value) = (wide(SUBKEY), wide(VALUE));
+        let mut data: u32 = 0;
+        let mut size = std::mem::size_of::<u32>() as u32;
+        let status = unsafe {
+            RegGetValueW(
+                HKEY_CURRENT_USER,
+                subkey.as_ptr(),
+                value.as_ptr(),
+                RRF_RT_REG_DWORD,
+                std::ptr::null_mut(),
+                &mut data as *mut u32 as *mut u8,
+                &mut size,
+            )
+        };
+        (status == 0).then_some(data == 0)
+    }
+
+    pub fn watch(on_change: impl Fn(bool)) {
+        let subkey = wide(SUBKEY);
+        let mut key: HKEY = 0;
+        if unsafe { RegOpenKeyExW(HKEY_CURRENT_USER, subkey.as_ptr(), 0, KEY_READ, &mut key) } != 0
+        {
+            return;
+        }
+        let mut last = prefers_dark();
+        // 同步等待：键下的值被修改时返回
+        while unsafe { RegNotifyChangeKeyValue(key, 0, REG_NOTIFY_CHANGE_LAST_SET, 0, 0) } == 0 {
+            let current = prefers_dark();
+            if current != last {
+                last = current;
+                on_change(current.unwrap_or(false));
+            }
+        }
+        unsafe {
+            RegCloseKey(key);
+        }
+    }
+}
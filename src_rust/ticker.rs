@@ -0,0 +1,88 @@
+//! 主计时器的唤醒调度：每次 tick 之后只在下一次可观察的变化时再醒来
+//!
+//! 取代固定 100ms 的重复定时器。窗口隐藏在托盘时，一次唤醒可以间隔一分钟。
+
+use slint::{Timer, TimerMode};
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+#[cfg(debug_assertions)]
+use std::time::Instant;
+
+/// debug 构建下每隔多久输出一次唤醒统计
+#[cfg(debug_assertions)]
+const STATS_INTERVAL: Duration = Duration::from_secs(60);
+
+type TickFn = Box<dyn FnMut() -> Duration>;
+
+pub struct Ticker {
+    timer: Timer,
+    /// 执行一次 tick，返回距下一次唤醒的时间
+    tick: RefCell<Option<TickFn>>,
+    this: Weak<Ticker>,
+    /// 累计唤醒次数
+    wakeups: Cell<u64>,
+    #[cfg(debug_assertions)]
+    stats: Cell<(Instant, u64)>,
+}
+
+impl Ticker {
+    pub fn new() -> Rc<Self> {
+        Rc::new_cyclic(|this| Self {
+            timer: Timer::default(),
+            tick: RefCell::new(None),
+            this: this.clone(),
+            wakeups: Cell::new(0),
+            #[cfg(debug_assertions)]
+            stats: Cell::new((Instant::now(), 0)),
+        })
+    }
+
+    /// 设置 tick 函数并立即执行第一次
+    pub fn start(&self, tick: impl FnMut() -> Duration + 'static) {
+        *self.tick.borrow_mut() = Some(Box::new(tick));
+        self.kick();
+    }
+
+    /// 状态被外部改变（暂停、显示窗口、修改设置…）后立即重新计算
+    pub fn kick(&self) {
+        self.schedule(Duration::ZERO);
+    }
+
+    fn schedule(&self, delay: Duration) {
+        let this = self.this.clone();
+        self.timer.start(TimerMode::SingleShot, delay, move || {
+            if let Some(ticker) = this.upgrade() {
+                ticker.fire();
+            }
+        });
+    }
+
+    fn fire(&self) {
+        self.wakeups.set(self.wakeups.get() + 1);
+        #[cfg(debug_assertions)]
+        self.report_stats();
+
+        let delay = match self.tick.borrow_mut().as_mut() {
+            Some(tick) => tick(),
+            None => return,
+        };
+        self.schedule(delay);
+    }
+
+    #[cfg(debug_assertions)]
+    fn report_stats(&self) {
+        let (since, count) = self.stats.get();
+        let elapsed = since.elapsed();
+        if elapsed >= STATS_INTERVAL {
+            let wakeups = self.wakeups.get();
+            eprintln!(
+                "[ticker] {} wakeups in {:.0?} ({} total)",
+                wakeups - count,
+                elapsed,
+                wakeups
+            );
+            self.stats.set((Instant::now(), wakeups));
+        }
+    }
+}
@@ -0,0 +1,251 @@
+//! 系统托盘：图标、菜单，以及随 AppState 变化刷新的状态显示
+
+use crate::config::TrayIconStyle;
+use crate::tray_render::{load_base_icon, render_icon, to_tray_icon, IconPhase};
+use image::RgbaImage;
+use std::cell::{Cell, RefCell};
+use std::time::Duration;
+use tray_icon::{
+    menu::{CheckMenuItem, Menu, MenuEvent, MenuId, MenuItem, PredefinedMenuItem, Submenu},
+    TrayIcon, TrayIconBuilder, TrayIconEvent,
+};
+
+/// 托盘菜单触发的操作
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TrayAction {
+    ShowWindow,
+    TogglePause,
+    Skip,
+    RestNow,
+    PauseFor(Duration),
+    PauseUntilTomorrow,
+    SelectProfile(usize),
+    ToggleTheme,
+    Quit,
+}
+
+/// 托盘图标或菜单产生的事件
+pub enum TrayEvent {
+    Menu(MenuEvent),
+    Icon(TrayIconEvent),
+}
+
+type Dispatcher = Box<dyn Fn(TrayEvent)>;
+
+thread_local! {
+    static DISPATCHER: RefCell<Option<Dispatcher>> = const { RefCell::new(None) };
+}
+
+/// 把托盘事件转发进 Slint 事件循环，由 `dispatch` 在 UI 线程上逐个处理
+///
+/// 需要在 UI 线程调用。tray-icon 可能在其他线程触发回调，那里拿不到 `Rc` 状态，
+/// 所以回调只负责 `invoke_from_event_loop`，处理函数保存在 UI 线程的线程局部变量中。
+pub fn install_event_handler(dispatch: impl Fn(TrayEvent) + 'static) {
+    DISPATCHER.with(|dispatcher| *dispatcher.borrow_mut() = Some(Box::new(dispatch)));
+    MenuEvent::set_event_handler(Some(|event| forward(TrayEvent::Menu(event))));
+    TrayIconEvent::set_event_handler(Some(|event| forward(TrayEvent::Icon(event))));
+}
+
+fn forward(event: TrayEvent) {
+    let _ = slint::invoke_from_event_loop(move || {
+        DISPATCHER.with(|dispatcher| {
+            if let Some(dispatch) = dispatcher.borrow().as_ref() {
+                dispatch(event);
+            }
+        });
+    });
+}
+
+/// 托盘需要展示的状态快照；只有变化时才刷新菜单
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrayStatus {
+    /// 表头与悬停提示，例如 "专注中 12:34"
+    pub summary: String,
+    pub paused: bool,
+    pub resting: bool,
+    pub can_skip: bool,
+    pub active_profile: usize,
+    pub dark_mode: bool,
+    pub phase: IconPhase,
+    /// 当前阶段剩余的比例，1.0 表示刚开始
+    pub progress: f32,
+    /// 剩余分钟数（向上取整）
+    pub minutes_left: u64,
+}
+
+pub struct Tray {
+    icon: TrayIcon,
+    header: MenuItem,
+    pause_item: MenuItem,
+    skip_item: MenuItem,
+    rest_now_item: MenuItem,
+    pause_30m_item: MenuItem,
+    pause_1h_item: MenuItem,
+    pause_tomorrow_item: MenuItem,
+    profile_items: Vec<CheckMenuItem>,
+    theme_item: CheckMenuItem,
+    show_item: MenuItem,
+    quit_item: MenuItem,
+    last_status: RefCell<Option<TrayStatus>>,
+    base_icon: RgbaImage,
+    icon_style: TrayIconStyle,
+    /// 上次绘制图标时的阶段与分钟数；两者不变就不重绘，即至多每分钟一次
+    last_icon: Cell<Option<(IconPhase, u64)>>,
+}
+
+impl Tray {
+    pub fn new(profile_names: &[String], active_profile: usize, icon_style: TrayIconStyle) -> Self {
+        let header = MenuItem::new("瞎了么", false, None);
+        let pause_item = MenuItem::new("暂停", true, None);
+        let skip_item = MenuItem::new("重新计时", true, None);
+        let rest_now_item = MenuItem::new("立即休息", true, None);
+
+        let pause_menu = Submenu::new("暂停一段时间", true);
+        let pause_30m_item = MenuItem::new("30 分钟", true, None);
+        let pause_1h_item = MenuItem::new("1 小时", true, None);
+        let pause_tomorrow_item = MenuItem::new("到明天", true, None);
+        pause_menu
+            .append_items(&[&pause_30m_item, &pause_1h_item, &pause_tomorrow_item])
+            .unwrap();
+
+        let profile_menu = Submenu::new("方案", true);
+        let profile_items: Vec<CheckMenuItem> = profile_names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| CheckMenuItem::new(name, true, index == active_profile, None))
+            .collect();
+        for item in &profile_items {
+            profile_menu.append(item).unwrap();
+        }
+
+        let theme_item = CheckMenuItem::new("深色主题", true, false, None);
+        let show_item = MenuItem::new("显示窗口", true, None);
+        let quit_item = MenuItem::new("退出", true, None);
+
+        let menu = Menu::new();
+        menu.append_items(&[
+            &header,
+            &PredefinedMenuItem::separator(),
+            &pause_item,
+            &skip_item,
+            &rest_now_item,
+            &pause_menu,
+            &PredefinedMenuItem::separator(),
+            &profile_menu,
+            &theme_item,
+            &PredefinedMenuItem::separator(),
+            &show_item,
+            &quit_item,
+        ])
+        .unwrap();
+
+        let base_icon = load_base_icon();
+        let icon = TrayIconBuilder::new()
+            .with_menu(Box::new(menu))
+            .with_tooltip("瞎了么")
+            .with_icon(to_tray_icon(base_icon.clone()))
+            .build()
+            .expect("Failed to create tray icon");
+
+        Self {
+            icon,
+            header,
+            pause_item,
+            skip_item,
+            rest_now_item,
+            pause_30m_item,
+            pause_1h_item,
+            pause_tomorrow_item,
+            profile_items,
+            theme_item,
+            show_item,
+            quit_item,
+            last_status: RefCell::new(None),
+            base_icon,
+            icon_style,
+            last_icon: Cell::new(None),
+        }
+    }
+
+    pub fn action(&self, id: &MenuId) -> Option<TrayAction> {
+        let action = if id == self.pause_item.id() {
+            TrayAction::TogglePause
+        } else if id == self.skip_item.id() {
+            TrayAction::Skip
+        } else if id == self.rest_now_item.id() {
+            TrayAction::RestNow
+        } else if id == self.pause_30m_item.id() {
+            TrayAction::PauseFor(Duration::from_secs(30 * 60))
+        } else if id == self.pause_1h_item.id() {
+            TrayAction::PauseFor(Duration::from_secs(60 * 60))
+        } else if id == self.pause_tomorrow_item.id() {
+            TrayAction::PauseUntilTomorrow
+        } else if id == self.theme_item.id() {
+            TrayAction::ToggleTheme
+        } else if id == self.show_item.id() {
+            TrayAction::ShowWindow
+        } else if id == self.quit_item.id() {
+            TrayAction::Quit
+        } else {
+            let index = self.profile_items.iter().position(|item| item.id() == id)?;
+            TrayAction::SelectProfile(index)
+        };
+        Some(action)
+    }
+
+    /// 丢弃缓存的状态，下次 `sync` 时完整刷新
+    pub fn invalidate(&self) {
+        self.last_status.borrow_mut().take();
+    }
+
+    fn sync_icon(&self, status: &TrayStatus) {
+        if self.icon_style == TrayIconStyle::Static {
+            return;
+        }
+        let key = (status.phase, status.minutes_left);
+        if self.last_icon.get() == Some(key) {
+            return;
+        }
+        self.last_icon.set(Some(key));
+
+        let image = render_icon(
+            &self.base_icon,
+            self.icon_style,
+            status.phase,
+            status.progress,
+            status.minutes_left,
+        );
+        let _ = self.icon.set_icon(Some(to_tray_icon(image)));
+    }
+
+    /// 按最新状态刷新菜单文字、勾选与提示
+    pub fn sync(&self, status: TrayStatus) {
+        let mut last_status = self.last_status.borrow_mut();
+        if last_status.as_ref() == Some(&status) {
+            return;
+        }
+
+        self.header.set_text(&status.summary);
+        let _ = self
+            .icon
+            .set_tooltip(Some(format!("瞎了么 · {}", status.summary)));
+
+        self.pause_item
+            .set_text(if status.paused { "继续" } else { "暂停" });
+        self.skip_item.set_text(if status.resting {
+            "跳过休息"
+        } else {
+            "重新计时"
+        });
+        self.skip_item.set_enabled(status.can_skip);
+        self.rest_now_item.set_enabled(!status.resting);
+
+        for (index, item) in self.profile_items.iter().enumerate() {
+            item.set_checked(index == status.active_profile);
+        }
+        self.theme_item.set_checked(status.dark_mode);
+        self.sync_icon(&status);
+
+        *last_status = Some(status);
+    }
+}
@@ -0,0 +1,180 @@
+//! 托盘图标的运行时绘制：在内嵌 PNG 上叠加进度圆环或剩余分钟数
+
+use crate::config::TrayIconStyle;
+use image::{imageops, Pixel, Rgba, RgbaImage};
+use std::f32::consts::TAU;
+
+/// 绘制尺寸；系统会再缩放到托盘实际大小
+const ICON_SIZE: u32 = 64;
+const RING_WIDTH: f32 = 7.0;
+
+/// 决定图标配色的计时阶段
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IconPhase {
+    Working,
+    Paused,
+    Resting,
+}
+
+impl IconPhase {
+    fn tint(self) -> Rgba<u8> {
+        match self {
+            IconPhase::Working => Rgba([0x22, 0xc5, 0x5e, 0xff]), // 与 AppPalette.accent 一致
+            IconPhase::Paused => Rgba([0xf5, 0x9e, 0x0b, 0xff]),
+            IconPhase::Resting => Rgba([0x3b, 0x82, 0xf6, 0xff]),
+        }
+    }
+}
+
+/// 解码内嵌的托盘图标 PNG
+pub fn load_base_icon() -> RgbaImage {
+    // Embed the logo PNG at compile time
+    let png_data = include_bytes!("../assets/tray-icon.png");
+    image::load_from_memory(png_data)
+        .expect("Failed to load tray icon")
+        .to_rgba8()
+}
+
+/// 按样式绘制图标；`progress` 为剩余比例（1.0 = 刚开始）
+pub fn render_icon(
+    base: &RgbaImage,
+    style: TrayIconStyle,
+    phase: IconPhase,
+    progress: f32,
+    minutes: u64,
+) -> RgbaImage {
+    match style {
+        TrayIconStyle::Static => base.clone(),
+        TrayIconStyle::Arc => render_arc(base, phase, progress),
+        TrayIconStyle::Digits => render_digits(base, phase, minutes),
+    }
+}
+
+pub fn to_tray_icon(image: RgbaImage) -> tray_icon::Icon {
+    let (width, height) = image.dimensions();
+    tray_icon::Icon::from_rgba(image.into_raw(), width, height).expect("Failed to create tray icon")
+}
+
+/// Logo 缩小放在中间，外圈从 12 点方向顺时针画剩余进度
+fn render_arc(base: &RgbaImage, phase: IconPhase, progress: f32) -> RgbaImage {
+    let mut canvas = RgbaImage::new(ICON_SIZE, ICON_SIZE);
+    let inner = ICON_SIZE - 2 * (RING_WIDTH as u32 + 2);
+    let logo = imageops::resize(base, inner, inner, imageops::FilterType::Triangle);
+    let offset = ((ICON_SIZE - inner) / 2) as i64;
+    imageops::overlay(&mut canvas, &logo, offset, offset);
+
+    let center = ICON_SIZE as f32 / 2.0;
+    let radius = center - RING_WIDTH / 2.0;
+    let progress = progress.clamp(0.0, 1.0);
+    let tint = phase.tint();
+    let track = Rgba([0x80, 0x80, 0x80, 0x60]);
+
+    for (x, y, pixel) in canvas.enumerate_pixels_mut() {
+        let dx = x as f32 + 0.5 - center;
+        let dy = y as f32 + 0.5 - center;
+        // 环带边缘做 1px 的抗锯齿
+        let coverage =
+            (RING_WIDTH / 2.0 + 0.5 - ((dx * dx + dy * dy).sqrt() - radius).abs()).clamp(0.0, 1.0);
+        if coverage <= 0.0 {
+            continue;
+        }
+
+        let angle = dx.atan2(-dy).rem_euclid(TAU) / TAU;
+        let mut color = if angle <= progress { tint } else { track };
+        color.0[3] = (color.0[3] as f32 * coverage) as u8;
+        pixel.blend(&color);
+    }
+    canvas
+}
+
+/// Logo 铺满，下半部分叠加带底色的剩余分钟数
+fn render_digits(base: &RgbaImage, phase: IconPhase, minutes: u64) -> RgbaImage {
+    let mut canvas = imageops::resize(base, ICON_SIZE, ICON_SIZE, imageops::FilterType::Triangle);
+
+    let text = minutes.min(999).to_string();
+    let columns = text.len() as u32 * (GLYPH_WIDTH + 1) - 1;
+    let badge_height = ICON_SIZE * 5 / 8;
+    let scale = ((ICON_SIZE - 4) / columns).min((badge_height - 6) / GLYPH_HEIGHT);
+
+    let badge_top = ICON_SIZE - badge_height;
+    fill_rounded_rect(
+        &mut canvas,
+        0,
+        badge_top,
+        ICON_SIZE,
+        badge_height,
+        8,
+        phase.tint(),
+    );
+
+    let text_width = columns * scale;
+    let text_height = GLYPH_HEIGHT * scale;
+    let mut left = (ICON_SIZE - text_width) / 2;
+    let top = badge_top + (badge_height - text_height) / 2;
+    for digit in text.bytes().map(|b| (b - b'0') as usize) {
+        draw_glyph(&mut canvas, &DIGITS[digit], left, top, scale);
+        left += (GLYPH_WIDTH + 1) * scale;
+    }
+    canvas
+}
+
+fn fill_rounded_rect(
+    canvas: &mut RgbaImage,
+    left: u32,
+    top: u32,
+    width: u32,
+    height: u32,
+    radius: u32,
+    color: Rgba<u8>,
+) {
+    let radius = radius as f32;
+    for y in top..top + height {
+        for x in left..left + width {
+            // 到最近圆角圆心的距离，只在四个角上生效
+            let cx = (x as f32 + 0.5).clamp(left as f32 + radius, (left + width) as f32 - radius);
+            let cy = (y as f32 + 0.5).clamp(top as f32 + radius, (top + height) as f32 - radius);
+            let distance = ((x as f32 + 0.5 - cx).powi(2) + (y as f32 + 0.5 - cy).powi(2)).sqrt();
+            let coverage = (radius + 0.5 - distance).clamp(0.0, 1.0);
+            if coverage > 0.0 {
+                let mut color = color;
+                color.0[3] = (color.0[3] as f32 * coverage) as u8;
+                canvas.get_pixel_mut(x, y).blend(&color);
+            }
+        }
+    }
+}
+
+const GLYPH_WIDTH: u32 = 3;
+const GLYPH_HEIGHT: u32 = 5;
+
+/// 3x5 点阵数字，每行低 3 位从左到右
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+fn draw_glyph(canvas: &mut RgbaImage, glyph: &[u8; 5], left: u32, top: u32, scale: u32) {
+    let white = Rgba([0xff, 0xff, 0xff, 0xff]);
+    for (row, bits) in glyph.iter().enumerate() {
+        for column in 0..GLYPH_WIDTH {
+            if bits & (1 << (GLYPH_WIDTH - 1 - column)) == 0 {
+                continue;
+            }
+            let x0 = left + column * scale;
+            let y0 = top + row as u32 * scale;
+            for y in y0..y0 + scale {
+                for x in x0..x0 + scale {
+                    canvas.put_pixel(x, y, white);
+                }
+            }
+        }
+    }
+}
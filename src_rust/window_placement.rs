@@ -0,0 +1,227 @@
+//! 主窗口的位置记忆：按显示器布局分别保存，布局变化后把窗口挪回屏幕内；迷你模式的贴边吸附
+//!
+//! 这里只做计算，不接触窗口；坐标均为虚拟桌面中的物理像素。
+
+use crate::overlay_layout::detail_monitor;
+use crate::platform::{MonitorRect, WorkArea};
+
+/// 显示器布局的标识，例如 `DP-1:1920x1080+0+0,HDMI-1:2560x1440+1920+0`
+///
+/// 与 `MockDisplays::parse` 的写法一致（不含缩放），同一组显示器的排列顺序不影响结果。
+pub fn layout_key(monitors: &[MonitorRect]) -> String {
+    let mut outputs: Vec<String> = monitors
+        .iter()
+        .map(|monitor| {
+            format!(
+                "{}:{}x{}+{}+{}",
+                monitor.name, monitor.width, monitor.height, monitor.x, monitor.y
+            )
+        })
+        .collect();
+    outputs.sort();
+    outputs.join(",")
+}
+
+/// 把窗口挪进与它重叠最多的显示器的可用区域；与所有显示器都不重叠时（例如所在的显示器
+/// 已拔掉）放到主显示器的中央。没有任何显示器信息时原样返回左上角。
+pub fn clamp_position(window: WorkArea, monitors: &[MonitorRect]) -> (i32, i32) {
+    let overlapping = monitors
+        .iter()
+        .filter_map(|monitor| {
+            let overlap = monitor.work_area.intersect(&window)?;
+            Some((monitor, overlap.width as u64 * overlap.height as u64))
+        })
+        .max_by_key(|(_, area)| *area)
+        .map(|(monitor, _)| monitor);
+
+    match overlapping {
+        Some(monitor) => (
+            clamp_axis(
+                window.x,
+                window.width,
+                monitor.work_area.x,
+                monitor.work_area.width,
+            ),
+            clamp_axis(
+                window.y,
+                window.height,
+                monitor.work_area.y,
+                monitor.work_area.height,
+            ),
+        ),
+        None => match detail_monitor(monitors) {
+            Some(monitor) => {
+                let area = monitor.work_area;
+                (
+                    area.x + (area.width as i32 - window.width as i32).max(0) / 2,
+                    area.y + (area.height as i32 - window.height as i32).max(0) / 2,
+                )
+            }
+            None => (window.x, window.y),
+        },
+    }
+}
+
+/// 窗口比可用区域大时贴住起始边，保证标题栏可以拖动
+fn clamp_axis(start: i32, length: u32, area_start: i32, area_length: u32) -> i32 {
+    let max = area_start + area_length as i32 - length as i32;
+    start.min(max).max(area_start)
+}
+
+/// 迷你模式的吸附：先挪回屏幕内，离所在显示器可用区域的边缘不到 `distance` 时贴到边上
+pub fn snap_to_edges(window: WorkArea, monitors: &[MonitorRect], distance: u32) -> (i32, i32) {
+    let (x, y) = clamp_position(window, monitors);
+    let Some(area) = area_at_center(x, y, window, monitors) else {
+        return (x, y);
+    };
+    (
+        snap_axis(x, window.width, area.x, area.width, distance),
+        snap_axis(y, window.height, area.y, area.height, distance),
+    )
+}
+
+/// 迷你小窗的默认位置：`window` 所在显示器可用区域的右上角，留出 `margin`
+pub fn top_right_corner(
+    window: WorkArea,
+    size: (u32, u32),
+    monitors: &[MonitorRect],
+    margin: u32,
+) -> (i32, i32) {
+    let (x, y) = clamp_position(window, monitors);
+    let area = area_at_center(x, y, window, monitors)
+        .or_else(|| detail_monitor(monitors).map(|monitor| monitor.work_area));
+    match area {
+        Some(area) => (
+            area.x + area.width as i32 - size.0 as i32 - margin as i32,
+            area.y + margin as i32,
+        ),
+        None => (x, y),
+    }
+}
+
+/// 窗口移到 (x, y) 后，其中心所在显示器的可用区域
+fn area_at_center(x: i32, y: i32, window: WorkArea, monitors: &[MonitorRect]) -> Option<WorkArea> {
+    let center = (x + window.width as i32 / 2, y + window.height as i32 / 2);
+    monitors
+        .iter()
+        .map(|monitor| monitor.work_area)
+        .find(|area| {
+            (area.x..area.x + area.width as i32).contains(&center.0)
+                && (area.y..area.y + area.height as i32).contains(&center.1)
+        })
+}
+
+fn snap_axis(start: i32, length: u32, area_start: i32, area_length: u32, distance: u32) -> i32 {
+    let end = area_start + area_length as i32 - length as i32;
+    if (start - area_start).unsigned_abs() <= distance {
+        area_start
+    } else if (end - start).unsigned_abs() <= distance {
+        end
+    } else {
+        start
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::{DisplayProvider, MockDisplays};
+
+    /// 左边 1920x1080 为主显示器，底部 40 像素是任务栏；右边 2560x1440
+    fn monitors() -> Vec<MonitorRect> {
+        let mut monitors = MockDisplays::parse("DP-1:1920x1080+0+0,HDMI-1:2560x1440+1920+0")
+            .unwrap()
+            .monitors();
+        monitors[0].work_area.height = 1040;
+        monitors
+    }
+
+    fn window(x: i32, y: i32, width: u32, height: u32) -> WorkArea {
+        WorkArea {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn keeps_window_that_fits() {
+        assert_eq!(
+            clamp_position(window(100, 200, 400, 300), &monitors()),
+            (100, 200)
+        );
+    }
+
+    #[test]
+    fn clamps_into_work_area_of_most_overlapping_monitor() {
+        // 大部分在左边显示器，下边压住任务栏
+        assert_eq!(
+            clamp_position(window(1600, 900, 400, 300), &monitors()),
+            (1520, 740)
+        );
+        // 大部分在右边显示器
+        assert_eq!(
+            clamp_position(window(1800, -50, 400, 300), &monitors()),
+            (1920, 0)
+        );
+    }
+
+    #[test]
+    fn centers_window_on_primary_when_off_screen() {
+        assert_eq!(
+            clamp_position(window(9000, 9000, 400, 300), &monitors()),
+            (760, 370)
+        );
+    }
+
+    #[test]
+    fn oversized_window_sticks_to_top_left() {
+        assert_eq!(
+            clamp_position(window(50, 50, 3000, 2000), &monitors()),
+            (0, 0)
+        );
+    }
+
+    #[test]
+    fn leaves_position_alone_without_monitors() {
+        assert_eq!(clamp_position(window(-500, 40, 400, 300), &[]), (-500, 40));
+    }
+
+    #[test]
+    fn snaps_to_nearby_edges() {
+        let monitors = monitors();
+        // 离左边 10、离上边 12
+        assert_eq!(
+            snap_to_edges(window(10, 12, 200, 100), &monitors, 16),
+            (0, 0)
+        );
+        // 离右边缘 8、离任务栏 5
+        assert_eq!(
+            snap_to_edges(window(1712, 935, 200, 100), &monitors, 16),
+            (1720, 940)
+        );
+        // 在右边显示器上贴左边
+        assert_eq!(
+            snap_to_edges(window(1930, 600, 200, 100), &monitors, 16),
+            (1920, 600)
+        );
+    }
+
+    #[test]
+    fn does_not_snap_beyond_distance() {
+        assert_eq!(
+            snap_to_edges(window(40, 300, 200, 100), &monitors(), 16),
+            (40, 300)
+        );
+    }
+
+    #[test]
+    fn layout_key_ignores_order() {
+        let mut monitors = monitors();
+        let key = layout_key(&monitors);
+        assert_eq!(key, "DP-1:1920x1080+0+0,HDMI-1:2560x1440+1920+0");
+        monitors.reverse();
+        assert_eq!(layout_key(&monitors), key);
+    }
+}